@@ -0,0 +1,21 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+// Passed by the callers that have no deadline, but need to give the arguments after it.
+pub const NO_DEADLINE: u64 = 0;
+
+#[elrond_wasm::module]
+pub trait DeadlineModule {
+    // The deadline is a block timestamp, after which the transaction is rejected.
+    fn require_deadline_not_passed(&self, opt_deadline: &OptionalArg<u64>) -> SCResult<()> {
+        if let OptionalArg::Some(deadline) = opt_deadline {
+            require!(
+                *deadline == NO_DEADLINE || self.blockchain().get_block_timestamp() <= *deadline,
+                "Deadline passed"
+            );
+        }
+        Ok(())
+    }
+}
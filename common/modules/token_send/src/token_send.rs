@@ -98,6 +98,34 @@ pub trait TokenSendModule {
             .into()
     }
 
+    fn transfer_egld_execute_custom(
+        &self,
+        to: &ManagedAddress,
+        amount: &BigUint,
+        opt_accept_funds_func: &OptionalArg<BoxedBytes>,
+    ) -> SCResult<()> {
+        if amount == &0 {
+            return Ok(());
+        }
+
+        let (function, gas_limit) = match opt_accept_funds_func {
+            OptionalArg::Some(accept_funds_func) => (
+                accept_funds_func.as_slice(),
+                self.transfer_exec_gas_limit().get(),
+            ),
+            OptionalArg::None => {
+                let no_func: &[u8] = &[];
+                (no_func, 0u64)
+            }
+        };
+        let arg_buffer = ManagedArgBuffer::new_empty(self.type_manager());
+        let endpoint_name = ManagedBuffer::new_from_bytes(self.type_manager(), function);
+
+        self.raw_vm_api()
+            .direct_egld_execute(to, amount, gas_limit, &endpoint_name, &arg_buffer)
+            .into()
+    }
+
     fn get_all_payments(&self) -> Vec<EsdtTokenPayment<Self::Api>> {
         self.raw_vm_api()
             .get_all_esdt_transfers()
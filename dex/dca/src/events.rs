@@ -0,0 +1,148 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::Position;
+
+#[derive(TopEncode)]
+pub struct CreatePositionEvent<M: ManagedTypeApi> {
+    position_id: u64,
+    owner: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    amount_per_execution: BigUint<M>,
+    interval: u64,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct ExecutePositionEvent<M: ManagedTypeApi> {
+    position_id: u64,
+    caller: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    amount_out: BigUint<M>,
+    remaining_amount: BigUint<M>,
+    next_execution: u64,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct CancelPositionEvent<M: ManagedTypeApi> {
+    position_id: u64,
+    owner: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    remaining_amount: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    accumulated_amount_out: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[elrond_wasm::module]
+pub trait EventsModule {
+    fn emit_create_position_event(&self, position_id: u64, position: &Position<Self::Api>) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.create_position_event(
+            &position.owner,
+            position_id,
+            epoch,
+            &CreatePositionEvent {
+                position_id,
+                owner: position.owner.clone(),
+                token_in: position.token_in.clone(),
+                amount: position.remaining_amount.clone(),
+                token_out: position.token_out.clone(),
+                amount_per_execution: position.amount_per_execution.clone(),
+                interval: position.interval,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_execute_position_event(
+        &self,
+        position_id: u64,
+        position: &Position<Self::Api>,
+        amount_in: &BigUint,
+        amount_out: &BigUint,
+    ) {
+        let caller = self.blockchain().get_caller();
+        let epoch = self.blockchain().get_block_epoch();
+        self.execute_position_event(
+            &position.owner,
+            &caller,
+            position_id,
+            epoch,
+            &ExecutePositionEvent {
+                position_id,
+                caller: caller.clone(),
+                token_in: position.token_in.clone(),
+                amount_in: amount_in.clone(),
+                token_out: position.token_out.clone(),
+                amount_out: amount_out.clone(),
+                remaining_amount: position.remaining_amount.clone(),
+                next_execution: position.next_execution,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_cancel_position_event(&self, position_id: u64, position: &Position<Self::Api>) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.cancel_position_event(
+            &position.owner,
+            position_id,
+            epoch,
+            &CancelPositionEvent {
+                position_id,
+                owner: position.owner.clone(),
+                token_in: position.token_in.clone(),
+                remaining_amount: position.remaining_amount.clone(),
+                token_out: position.token_out.clone(),
+                accumulated_amount_out: position.accumulated_amount_out.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    #[event("create_position")]
+    fn create_position_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] position_id: u64,
+        #[indexed] epoch: u64,
+        create_position_event: &CreatePositionEvent<Self::Api>,
+    );
+
+    #[event("execute_position")]
+    fn execute_position_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] position_id: u64,
+        #[indexed] epoch: u64,
+        execute_position_event: &ExecutePositionEvent<Self::Api>,
+    );
+
+    #[event("cancel_position")]
+    fn cancel_position_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] position_id: u64,
+        #[indexed] epoch: u64,
+        cancel_position_event: &CancelPositionEvent<Self::Api>,
+    );
+}
@@ -0,0 +1,362 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+mod events;
+
+use deadline::NO_DEADLINE;
+use router::pair_manager::SwapOperationType;
+
+use router::pair_manager::ProxyTrait as _;
+
+const MAX_SLIPPAGE: u64 = 10_000;
+const ACCEPT_PAY_FUNC_NAME: &[u8] = b"acceptPay";
+const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
+
+type Nonce = u64;
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, TypeAbi)]
+pub enum IntervalUnit {
+    Blocks,
+    Epochs,
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct SwapStep<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_wanted: TokenIdentifier<M>,
+}
+
+// The slippage is in basis points of the amount out expected from the pairs at execution time.
+// The minimum amount out is set by the owner for a whole amount per execution, and bounds the
+// output of every execution whatever the state of the pairs, since anyone can execute.
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct Position<M: ManagedTypeApi> {
+    pub owner: ManagedAddress<M>,
+    pub token_in: TokenIdentifier<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub path: Vec<SwapStep<M>>,
+    pub amount_per_execution: BigUint<M>,
+    pub remaining_amount: BigUint<M>,
+    pub accumulated_amount_out: BigUint<M>,
+    pub interval: u64,
+    pub interval_unit: IntervalUnit,
+    pub next_execution: u64,
+    pub max_slippage: u64,
+    pub min_amount_out: BigUint<M>,
+    pub paused: bool,
+}
+
+#[elrond_wasm::contract]
+pub trait Dca: events::EventsModule {
+    #[init]
+    fn init(&self, router_address: ManagedAddress) -> SCResult<()> {
+        require!(
+            self.blockchain().is_smart_contract(&router_address),
+            "Invalid router address"
+        );
+        self.router_address().set(&router_address);
+        self.state().set_if_empty(&true);
+        Ok(())
+    }
+
+    // The swap operations are given as for Router::multiPairSwap. Only fixed input swaps
+    // are supported, since the amount wanted of each one is computed at execution time.
+    #[payable("*")]
+    #[endpoint(createPosition)]
+    fn create_position(
+        &self,
+        #[payment_token] token_in: TokenIdentifier,
+        #[payment_amount] amount: BigUint,
+        #[payment_nonce] nonce: Nonce,
+        amount_per_execution: BigUint,
+        interval: u64,
+        interval_unit: IntervalUnit,
+        max_slippage: u64,
+        min_amount_out: BigUint,
+        #[var_args] swap_operations: MultiArgVec<SwapOperationType<Self::Api>>,
+    ) -> SCResult<u64> {
+        require!(self.state().get(), "Not active");
+        require!(!token_in.is_egld(), "Invalid token in");
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount > 0, "Invalid amount. Should not be zero");
+        require!(
+            amount_per_execution > 0 && amount_per_execution <= amount,
+            "Invalid amount per execution"
+        );
+        require!(interval > 0, "Invalid interval");
+        require!(max_slippage < MAX_SLIPPAGE, "Invalid max slippage");
+        require!(min_amount_out > 0, "Invalid min amount out");
+        require!(
+            !swap_operations.is_empty(),
+            "Invalid swap operations chain. Should not be empty"
+        );
+
+        let mut path = Vec::new();
+        for entry in swap_operations.into_vec() {
+            let (pair_address, function, token_wanted, _) = entry.into_tuple();
+            require!(
+                function == BoxedBytes::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                "Only swapTokensFixedInput operations are supported"
+            );
+            path.push(SwapStep {
+                pair_address,
+                token_wanted,
+            });
+        }
+        let token_out = path[path.len() - 1].token_wanted.clone();
+        require!(token_in != token_out, "Tokens should differ");
+
+        let position_id = self.last_position_id().get() + 1;
+        self.last_position_id().set(&position_id);
+
+        let position = Position {
+            owner: self.blockchain().get_caller(),
+            token_in,
+            token_out,
+            path,
+            amount_per_execution,
+            remaining_amount: amount,
+            accumulated_amount_out: BigUint::zero(),
+            interval,
+            interval_unit,
+            next_execution: self.get_current_interval_point(interval_unit),
+            max_slippage,
+            min_amount_out,
+            paused: false,
+        };
+        self.emit_create_position_event(position_id, &position);
+        self.positions(position_id).set(&position);
+
+        Ok(position_id)
+    }
+
+    // Permissionless. Swaps the amount per execution, or what is left of the deposit,
+    // through Router::multiPairSwap and keeps the output for the owner to withdraw.
+    #[endpoint]
+    fn execute(&self, position_id: u64) -> SCResult<()> {
+        require!(self.state().get(), "Not active");
+        require!(
+            !self.positions(position_id).is_empty(),
+            "Position not found"
+        );
+        let mut position = self.positions(position_id).get();
+        require!(!position.paused, "Position is paused");
+        require!(position.remaining_amount > 0, "Nothing left to swap");
+
+        let current_point = self.get_current_interval_point(position.interval_unit);
+        require!(
+            current_point >= position.next_execution,
+            "Execution is not due yet"
+        );
+
+        let amount_in = if position.remaining_amount < position.amount_per_execution {
+            position.remaining_amount.clone()
+        } else {
+            position.amount_per_execution.clone()
+        };
+        let (swap_operations, amount_out_min) = self.build_swap_operations(&position, &amount_in);
+        let position_amount_out_min =
+            &position.min_amount_out * &amount_in / &position.amount_per_execution;
+        let min_final_amount = if amount_out_min > position_amount_out_min {
+            amount_out_min
+        } else {
+            position_amount_out_min
+        };
+
+        let balance_before = self.blockchain().get_sc_balance(&position.token_out, 0);
+        self.router_contract_proxy(self.router_address().get())
+            .multi_pair_swap(
+                position.token_in.clone(),
+                amount_in.clone(),
+                0,
+                min_final_amount,
+                NO_DEADLINE,
+                swap_operations.into(),
+                OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
+            )
+            .execute_on_dest_context_ignore_result();
+        let amount_out = self.blockchain().get_sc_balance(&position.token_out, 0) - balance_before;
+
+        position.remaining_amount -= &amount_in;
+        position.accumulated_amount_out += &amount_out;
+        position.next_execution = current_point + position.interval;
+
+        self.emit_execute_position_event(position_id, &position, &amount_in, &amount_out);
+        self.positions(position_id).set(&position);
+        Ok(())
+    }
+
+    #[endpoint]
+    fn withdraw(&self, position_id: u64) -> SCResult<()> {
+        let mut position = self.get_owned_position(position_id)?;
+        require!(position.accumulated_amount_out > 0, "Nothing to withdraw");
+
+        let amount_out = core::mem::replace(&mut position.accumulated_amount_out, BigUint::zero());
+        self.send()
+            .direct(&position.owner, &position.token_out, 0, &amount_out, &[]);
+
+        if position.remaining_amount == 0 {
+            self.positions(position_id).clear();
+        } else {
+            self.positions(position_id).set(&position);
+        }
+        Ok(())
+    }
+
+    #[endpoint(pausePosition)]
+    fn pause_position(&self, position_id: u64) -> SCResult<()> {
+        let mut position = self.get_owned_position(position_id)?;
+        require!(!position.paused, "Position already paused");
+        position.paused = true;
+        self.positions(position_id).set(&position);
+        Ok(())
+    }
+
+    #[endpoint(setPositionMinAmountOut)]
+    fn set_position_min_amount_out(
+        &self,
+        position_id: u64,
+        min_amount_out: BigUint,
+    ) -> SCResult<()> {
+        require!(min_amount_out > 0, "Invalid min amount out");
+        let mut position = self.get_owned_position(position_id)?;
+        position.min_amount_out = min_amount_out;
+        self.positions(position_id).set(&position);
+        Ok(())
+    }
+
+    #[endpoint(resumePosition)]
+    fn resume_position(&self, position_id: u64) -> SCResult<()> {
+        let mut position = self.get_owned_position(position_id)?;
+        require!(position.paused, "Position not paused");
+        position.paused = false;
+        self.positions(position_id).set(&position);
+        Ok(())
+    }
+
+    // Sends back both the tokens not swapped yet and the output not withdrawn yet.
+    #[endpoint(cancelPosition)]
+    fn cancel_position(&self, position_id: u64) -> SCResult<()> {
+        let position = self.get_owned_position(position_id)?;
+        self.positions(position_id).clear();
+
+        if position.remaining_amount > 0 {
+            self.send().direct(
+                &position.owner,
+                &position.token_in,
+                0,
+                &position.remaining_amount,
+                &[],
+            );
+        }
+        if position.accumulated_amount_out > 0 {
+            self.send().direct(
+                &position.owner,
+                &position.token_out,
+                0,
+                &position.accumulated_amount_out,
+                &[],
+            );
+        }
+
+        self.emit_cancel_position_event(position_id, &position);
+        Ok(())
+    }
+
+    #[payable("*")]
+    #[endpoint(acceptPay)]
+    fn accept_pay(&self) {}
+
+    #[only_owner]
+    #[endpoint]
+    fn pause(&self) -> SCResult<()> {
+        self.state().set(&false);
+        Ok(())
+    }
+
+    #[only_owner]
+    #[endpoint]
+    fn resume(&self) -> SCResult<()> {
+        self.state().set(&true);
+        Ok(())
+    }
+
+    fn get_owned_position(&self, position_id: u64) -> SCResult<Position<Self::Api>> {
+        require!(
+            !self.positions(position_id).is_empty(),
+            "Position not found"
+        );
+        let position = self.positions(position_id).get();
+        require!(
+            self.blockchain().get_caller() == position.owner,
+            "Not the position owner"
+        );
+        Ok(position)
+    }
+
+    fn get_current_interval_point(&self, interval_unit: IntervalUnit) -> u64 {
+        match interval_unit {
+            IntervalUnit::Blocks => self.blockchain().get_block_nonce(),
+            IntervalUnit::Epochs => self.blockchain().get_block_epoch(),
+        }
+    }
+
+    // The amount wanted of each swap is the amount out given by the pair, minus the slippage.
+    // The one of the last swap is also returned, as the minimum final amount of the path.
+    fn build_swap_operations(
+        &self,
+        position: &Position<Self::Api>,
+        amount_in: &BigUint,
+    ) -> (Vec<SwapOperationType<Self::Api>>, BigUint) {
+        let mut swap_operations = Vec::new();
+        let mut min_amount_out = BigUint::zero();
+        let mut token_in = position.token_in.clone();
+        let mut amount_in = amount_in.clone();
+        for step in position.path.iter() {
+            let amount_out: BigUint = self
+                .pair_contract_proxy(step.pair_address.clone())
+                .get_amount_out_view(token_in, amount_in)
+                .execute_on_dest_context();
+            let amount_out_min =
+                &amount_out * (MAX_SLIPPAGE - position.max_slippage) / MAX_SLIPPAGE;
+
+            swap_operations.push(
+                (
+                    step.pair_address.clone(),
+                    BoxedBytes::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    step.token_wanted.clone(),
+                    amount_out_min.clone(),
+                )
+                    .into(),
+            );
+            min_amount_out = amount_out_min;
+            token_in = step.token_wanted.clone();
+            amount_in = amount_out;
+        }
+        (swap_operations, min_amount_out)
+    }
+
+    #[proxy]
+    fn router_contract_proxy(&self, to: ManagedAddress) -> router::Proxy<Self::Api>;
+
+    #[proxy]
+    fn pair_contract_proxy(&self, to: ManagedAddress) -> pair::Proxy<Self::Api>;
+
+    #[view(getRouterAddress)]
+    #[storage_mapper("router_address")]
+    fn router_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getState)]
+    #[storage_mapper("state")]
+    fn state(&self) -> SingleValueMapper<bool>;
+
+    #[view(getPosition)]
+    #[storage_mapper("positions")]
+    fn positions(&self, position_id: u64) -> SingleValueMapper<Position<Self::Api>>;
+
+    #[view(getLastPositionId)]
+    #[storage_mapper("last_position_id")]
+    fn last_position_id(&self) -> SingleValueMapper<u64>;
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+pub use dca::*;
+pub use elrond_wasm_output::*;
@@ -0,0 +1,60 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+// Minimal stand-in for the EGLD wrapping contract, exchanging EGLD and wrapped EGLD 1:1
+// out of its own balances. Only meant for testing the contracts that integrate with it.
+#[elrond_wasm::contract]
+pub trait EgldWrapperMock {
+    #[init]
+    fn init(&self, wrapped_egld_token_id: TokenIdentifier) -> SCResult<()> {
+        require!(
+            wrapped_egld_token_id.is_valid_esdt_identifier(),
+            "Invalid wrapped EGLD token ID"
+        );
+        self.wrapped_egld_token_id().set(&wrapped_egld_token_id);
+        Ok(())
+    }
+
+    #[payable("EGLD")]
+    #[endpoint(wrapEgld)]
+    fn wrap_egld(
+        &self,
+        #[payment_amount] payment_amount: BigUint,
+    ) -> SCResult<EsdtTokenPayment<Self::Api>> {
+        require!(payment_amount > 0, "Payment must be more than 0");
+
+        let wrapped_egld_token_id = self.wrapped_egld_token_id().get();
+        let caller = self.blockchain().get_caller();
+        self.send()
+            .direct(&caller, &wrapped_egld_token_id, 0, &payment_amount, &[]);
+
+        Ok(EsdtTokenPayment::from(
+            wrapped_egld_token_id,
+            0,
+            payment_amount,
+        ))
+    }
+
+    #[payable("*")]
+    #[endpoint(unwrapEgld)]
+    fn unwrap_egld(
+        &self,
+        #[payment_token] payment_token: TokenIdentifier,
+        #[payment_amount] payment_amount: BigUint,
+    ) -> SCResult<()> {
+        require!(payment_amount > 0, "Must pay more than 0 tokens!");
+        require!(
+            payment_token == self.wrapped_egld_token_id().get(),
+            "Wrong esdt token"
+        );
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_egld(&caller, &payment_amount, &[]);
+        Ok(())
+    }
+
+    #[view(getWrappedEgldTokenId)]
+    #[storage_mapper("wrapped_egld_token_id")]
+    fn wrapped_egld_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+pub use egld_wrapper_mock::*;
+pub use elrond_wasm_output::*;
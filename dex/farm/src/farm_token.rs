@@ -77,7 +77,7 @@ pub trait FarmTokenModule:
 
                 let (returned_tokens, token_id) = self.call_value().payment_token_pair();
                 if token_id.is_egld() && returned_tokens > 0 {
-                    let _ = self.send().direct_egld(caller, &returned_tokens, &[]);
+                    self.send().direct_egld(caller, &returned_tokens, &[]);
                 }
             }
         }
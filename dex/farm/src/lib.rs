@@ -52,6 +52,7 @@ pub trait Farm:
         locked_asset_factory_address: ManagedAddress,
         division_safety_constant: BigUint,
         pair_contract_address: ManagedAddress,
+        #[var_args] opt_owner_address: OptionalArg<ManagedAddress>,
     ) -> SCResult<()> {
         require!(
             reward_token_id.is_valid_esdt_identifier(),
@@ -87,7 +88,12 @@ pub trait Farm:
         self.division_safety_constant()
             .set_if_empty(&division_safety_constant);
 
-        self.owner().set(&self.blockchain().get_caller());
+        // Farms deployed by the Router are owned by the Router's owner instead.
+        let owner = match opt_owner_address {
+            OptionalArg::Some(owner_address) => owner_address,
+            OptionalArg::None => self.blockchain().get_caller(),
+        };
+        self.owner().set(&owner);
         self.router_address().set(&router_address);
         self.reward_token_id().set(&reward_token_id);
         self.farming_token_id().set(&farming_token_id);
@@ -0,0 +1,58 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+mod pair_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait Pair {
+        #[payable("*")]
+        #[endpoint(repayFlashSwap)]
+        fn repay_flash_swap(
+            &self,
+            #[payment_token] token_id: TokenIdentifier,
+            #[payment_amount] amount: BigUint,
+        );
+    }
+}
+
+// Minimal flash swap receiver, paying back a preset amount of a preset token out of its own
+// balance. Only meant for testing the Pair flash swaps.
+#[elrond_wasm::contract]
+pub trait FlashSwapReceiverMock {
+    #[init]
+    fn init(&self) {}
+
+    #[endpoint(setRepayment)]
+    fn set_repayment(&self, token_id: TokenIdentifier, amount: BigUint) {
+        self.repay_token_id().set(&token_id);
+        self.repay_amount().set(&amount);
+    }
+
+    #[payable("*")]
+    #[endpoint(flashSwapCallback)]
+    fn flash_swap_callback(
+        &self,
+        #[payment_token] _token_out: TokenIdentifier,
+        #[payment_amount] _amount_out: BigUint,
+        _caller: ManagedAddress,
+        _user_data: BoxedBytes,
+    ) {
+        let pair_address = self.blockchain().get_caller();
+        self.pair_proxy(pair_address)
+            .repay_flash_swap(self.repay_token_id().get(), self.repay_amount().get())
+            .execute_on_dest_context();
+    }
+
+    #[proxy]
+    fn pair_proxy(&self, to: ManagedAddress) -> pair_proxy::Proxy<Self::Api>;
+
+    #[view(getRepayTokenId)]
+    #[storage_mapper("repay_token_id")]
+    fn repay_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getRepayAmount)]
+    #[storage_mapper("repay_amount")]
+    fn repay_amount(&self) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+pub use flash_swap_receiver_mock::*;
+pub use elrond_wasm_output::*;
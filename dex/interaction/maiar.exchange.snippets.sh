@@ -200,14 +200,15 @@ unbond() {
 
 # params:
 #   $1 = First Token Identifier,
-#   $2 = Second Token Identifier
+#   $2 = Second Token Identifier,
+#   $3 = Fee Tier (total fee percent, optional)
 getPairAddress() {
     first_token="0x$(echo -n $1 | xxd -p -u | tr -d '\n')"
     second_token="0x$(echo -n $2 | xxd -p -u | tr -d '\n')"
     erdpy --verbose contract query ${ROUTE_ADDRESS} \
         --proxy=${PROXY} \
         --function=getPair \
-        --arguments $first_token $second_token || return 
+        --arguments $first_token $second_token $3 || return 
 }
 
 # params:
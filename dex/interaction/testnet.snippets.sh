@@ -93,6 +93,7 @@ deployRouterContract() {
           --gas-limit=1499999999 \
           --proxy=${PROXY} --chain=${CHAIN_ID} \
           --bytecode="../router/output/router.wasm" \
+          --metadata-payable \
           --outfile="deploy-route-internal.interaction.json" --send || return
     
     ADDRESS=$(erdpy data parse --file="deploy-route-internal.interaction.json" --expression="data['emitted_tx']['address']")
@@ -109,6 +110,7 @@ upgradeRouterContract() {
           --gas-limit=${DEPLOY_GAS} \
           --proxy=${PROXY} --chain=${CHAIN_ID} \
           --bytecode="../router/output/router.wasm" \
+          --metadata-payable \
           --outfile="upgrade-route-internal.interaction.json" --send || return
 
     echo ""
@@ -197,7 +199,7 @@ createPair() {
     erdpy --verbose contract query ${ROUTE_ADDRESS} \
     --proxy=${PROXY} \
     --function=getPair \
-    --arguments $first_token $second_token || return
+    --arguments $first_token $second_token 300 || return
 }
 
 # params:
@@ -233,6 +235,84 @@ setLpTokenLocalRoles() {
         --send || return
 }
 
+# params:
+#   $1 = Pair Address,
+#   $2 = EGLD value (the LP token issue cost when retrying the issue, 0 otherwise)
+continuePairSetup() {
+    pair_address="0x$(erdpy wallet bech32 --decode $1)"
+
+    erdpy --verbose contract call ${ROUTE_ADDRESS} --recall-nonce \
+        --pem=${WALLET_PEM} \
+        --gas-limit=200000000 \
+        --value=$2 \
+        --proxy=${PROXY} --chain=${CHAIN_ID} \
+        --function="continuePairSetup" \
+        --arguments $pair_address \
+        --send || return
+}
+
+# params:
+#   $1 = Pair Address
+getPairSetupStatus() {
+    pair_address="0x$(erdpy wallet bech32 --decode $1)"
+    erdpy --verbose contract query ${ROUTE_ADDRESS} \
+        --proxy=${PROXY} \
+        --function=getPairSetupStatus \
+        --arguments $pair_address || return
+}
+
+# params:
+#   $1 = Gas per pair
+upgradeAllPairs() {
+    erdpy --verbose contract call ${ROUTE_ADDRESS} --recall-nonce \
+        --pem=${WALLET_PEM} \
+        --gas-limit=1400000000 \
+        --proxy=${PROXY} --chain=${CHAIN_ID} \
+        --function="upgradeAllPairs" \
+        --arguments $1 \
+        --send || return
+}
+
+getPairUpgradeProgress() {
+    erdpy --verbose contract query ${ROUTE_ADDRESS} \
+        --proxy=${PROXY} \
+        --function=getPairUpgradeProgress || return
+}
+
+# params:
+#   $1 = Pair Address
+deprecatePair() {
+    pair_address="0x$(erdpy wallet bech32 --decode $1)"
+
+    erdpy --verbose contract call ${ROUTE_ADDRESS} --recall-nonce \
+        --pem=${WALLET_PEM} \
+        --gas-limit=50000000 \
+        --proxy=${PROXY} --chain=${CHAIN_ID} \
+        --function="deprecatePair" \
+        --arguments $pair_address \
+        --send || return
+}
+
+# params:
+#   $1 = Pair Address
+removeDeprecatedPair() {
+    pair_address="0x$(erdpy wallet bech32 --decode $1)"
+
+    erdpy --verbose contract call ${ROUTE_ADDRESS} --recall-nonce \
+        --pem=${WALLET_PEM} \
+        --gas-limit=50000000 \
+        --proxy=${PROXY} --chain=${CHAIN_ID} \
+        --function="removeDeprecatedPair" \
+        --arguments $pair_address \
+        --send || return
+}
+
+getDeprecatedPairs() {
+    erdpy --verbose contract query ${ROUTE_ADDRESS} \
+        --proxy=${PROXY} \
+        --function=getDeprecatedPairs || return
+}
+
 #params:
 #   $1 = pair contract to send fees,
 #   $2 = farm contract to receive fees,
@@ -303,6 +383,7 @@ deployPairContract() {
           --proxy=${PROXY} --chain=${CHAIN_ID} \
           --bytecode="../pair/output/pair.wasm" \
           --arguments $first_token $second_token $router_address $user_address_decode 0x000000000000012C 0x0000000000000032 \
+          --metadata-payable \
           --outfile="deploy-pair-internal.interaction.json" --send || return
     
     ADDRESS=$(erdpy data parse --file="deploy-pair-internal.interaction.json" --expression="data['emitted_tx']['address']")
@@ -328,6 +409,7 @@ upgradePairContract() {
           --proxy=${PROXY} --chain=${CHAIN_ID} \
           --bytecode="../pair/output/pair.wasm" \
           --arguments $first_token $second_token $router_address $user_address_decode 0x000000000000012C 0x0000000000000032 \
+          --metadata-payable \
           --outfile="upgrade-pair-internal.interaction.json" --send || return
     
     echo ""
@@ -444,16 +526,18 @@ swapFixedOutput() {
 # params:
 #   $1  = Payment Token Identifier in hex
 #   $2  = Payment amount in hex
+#   $3  = Minimum final amount in hex
+#   $4  = Deadline block timestamp in hex, 0x00 for none
 #
-#   $3  = Pair Address in hex
-#   $4  = Swap Function to call in hex
-#   $5  = Arg 1 to Swap Function in hex
-#   $6  = Arg 2 to Swap Function in hex
+#   $5  = Pair Address in hex
+#   $6  = Swap Function to call in hex
+#   $7  = Arg 1 to Swap Function in hex
+#   $8  = Arg 2 to Swap Function in hex
 #
-#   $7  = Pair Address in hex
-#   $8  = Swap Function to call in hex
-#   $9  = Arg 1 to Swap Function in hex
-#   $10 = Arg 2 to Swap Function in hex
+#   $9  = Pair Address in hex
+#   $10 = Swap Function to call in hex
+#   $11 = Arg 1 to Swap Function in hex
+#   $12 = Arg 2 to Swap Function in hex
 #
 # Swap Function should be either "swapTokensFixedInput" or "swapTokensFixedOutput"
 #   Arg 1 to Swap Function is always a TokenIdentifier
@@ -467,7 +551,7 @@ multiPairSwap() {
       --gas-limit=100000000 \
       --proxy=${PROXY} --chain=${CHAIN_ID} \
       --function="ESDTTransfer" \
-      --arguments $1 $2 $method_name $3 $4 $5 $6 $7 $8 $9 ${10} \
+      --arguments $1 $2 $method_name $3 $4 $5 $6 $7 $8 $9 ${10} ${11} ${12} \
       --send || return
 }
 
@@ -690,14 +774,27 @@ startProduceRewards() {
 
 # params:
 #   $1 = First Token Identifier,
-#   $2 = Second Token Identifier
+#   $2 = Second Token Identifier,
+#   $3 = Fee Tier (total fee percent)
 getPairAddress() {
     first_token="0x$(echo -n $1 | xxd -p -u | tr -d '\n')"
     second_token="0x$(echo -n $2 | xxd -p -u | tr -d '\n')"
     erdpy --verbose contract query ${ROUTE_ADDRESS} \
         --proxy=${PROXY} \
         --function=getPair \
-        --arguments $first_token $second_token || return 
+        --arguments $first_token $second_token $3 || return 
+}
+
+# params:
+#   $1 = First Token Identifier,
+#   $2 = Second Token Identifier
+getPairFeeTiers() {
+    first_token="0x$(echo -n $1 | xxd -p -u | tr -d '\n')"
+    second_token="0x$(echo -n $2 | xxd -p -u | tr -d '\n')"
+    erdpy --verbose contract query ${ROUTE_ADDRESS} \
+        --proxy=${PROXY} \
+        --function=getPairFeeTiers \
+        --arguments $first_token $second_token || return
 }
 
 # params:
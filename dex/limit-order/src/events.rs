@@ -0,0 +1,146 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::Order;
+
+#[derive(TopEncode)]
+pub struct CreateOrderEvent<M: ManagedTypeApi> {
+    order_id: u64,
+    owner: ManagedAddress<M>,
+    pair_address: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    min_amount_out: BigUint<M>,
+    expiry: u64,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct FillOrderEvent<M: ManagedTypeApi> {
+    order_id: u64,
+    owner: ManagedAddress<M>,
+    keeper: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    amount_out: BigUint<M>,
+    keeper_fee_amount: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct CancelOrderEvent<M: ManagedTypeApi> {
+    order_id: u64,
+    owner: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[elrond_wasm::module]
+pub trait EventsModule {
+    fn emit_create_order_event(&self, order_id: u64, order: &Order<Self::Api>) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.create_order_event(
+            &order.owner,
+            order_id,
+            epoch,
+            &CreateOrderEvent {
+                order_id,
+                owner: order.owner.clone(),
+                pair_address: order.pair_address.clone(),
+                token_in: order.token_in.clone(),
+                amount_in: order.amount_in.clone(),
+                token_out: order.token_out.clone(),
+                min_amount_out: order.min_amount_out.clone(),
+                expiry: order.expiry,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_fill_order_event(
+        &self,
+        order_id: u64,
+        order: &Order<Self::Api>,
+        keeper: &ManagedAddress,
+        amount_out: &BigUint,
+        keeper_fee_amount: &BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.fill_order_event(
+            &order.owner,
+            keeper,
+            order_id,
+            epoch,
+            &FillOrderEvent {
+                order_id,
+                owner: order.owner.clone(),
+                keeper: keeper.clone(),
+                token_in: order.token_in.clone(),
+                amount_in: order.amount_in.clone(),
+                token_out: order.token_out.clone(),
+                amount_out: amount_out.clone(),
+                keeper_fee_amount: keeper_fee_amount.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_cancel_order_event(&self, order_id: u64, order: &Order<Self::Api>) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.cancel_order_event(
+            &order.owner,
+            order_id,
+            epoch,
+            &CancelOrderEvent {
+                order_id,
+                owner: order.owner.clone(),
+                token_in: order.token_in.clone(),
+                amount_in: order.amount_in.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    #[event("create_order")]
+    fn create_order_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] order_id: u64,
+        #[indexed] epoch: u64,
+        create_order_event: &CreateOrderEvent<Self::Api>,
+    );
+
+    #[event("fill_order")]
+    fn fill_order_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] keeper: &ManagedAddress,
+        #[indexed] order_id: u64,
+        #[indexed] epoch: u64,
+        fill_order_event: &FillOrderEvent<Self::Api>,
+    );
+
+    #[event("cancel_order")]
+    fn cancel_order_event(
+        &self,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] order_id: u64,
+        #[indexed] epoch: u64,
+        cancel_order_event: &CancelOrderEvent<Self::Api>,
+    );
+}
@@ -0,0 +1,222 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+mod events;
+
+use pair::liquidity_pool::ProxyTrait as _;
+
+const MAX_KEEPER_FEE: u64 = 10_000;
+const ACCEPT_PAY_FUNC_NAME: &[u8] = b"acceptPay";
+
+type Nonce = u64;
+
+// The limit price is given as the minimum amount of token out for the whole amount in,
+// after the keeper fee is deducted. The keeper fee is in basis points of the amount out.
+// The pair is the one registered in the Router for the two tokens when the order is created.
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct Order<M: ManagedTypeApi> {
+    pub owner: ManagedAddress<M>,
+    pub pair_address: ManagedAddress<M>,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub min_amount_out: BigUint<M>,
+    pub expiry: u64,
+}
+
+#[elrond_wasm::contract]
+pub trait LimitOrder: events::EventsModule {
+    #[init]
+    fn init(&self, keeper_fee: u64, router_address: ManagedAddress) -> SCResult<()> {
+        require!(keeper_fee < MAX_KEEPER_FEE, "Invalid keeper fee");
+        require!(
+            self.blockchain().is_smart_contract(&router_address),
+            "Invalid router address"
+        );
+        self.keeper_fee().set(&keeper_fee);
+        self.router_address().set(&router_address);
+        Ok(())
+    }
+
+    #[payable("*")]
+    #[endpoint(createOrder)]
+    fn create_order(
+        &self,
+        #[payment_token] token_in: TokenIdentifier,
+        #[payment_amount] amount_in: BigUint,
+        #[payment_nonce] nonce: Nonce,
+        token_out: TokenIdentifier,
+        min_amount_out: BigUint,
+        expiry: u64,
+        #[var_args] opt_fee_tier: OptionalArg<u64>,
+    ) -> SCResult<u64> {
+        require!(!token_in.is_egld(), "Invalid token in");
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount_in > 0, "Invalid amount. Should not be zero");
+        require!(token_in != token_out, "Tokens should differ");
+        require!(min_amount_out > 0, "Invalid min amount out");
+        require!(
+            expiry > self.blockchain().get_block_timestamp(),
+            "Invalid expiry"
+        );
+        let pair_address = self.get_pair_address(&token_in, &token_out, opt_fee_tier)?;
+
+        let order_id = self.last_order_id().get() + 1;
+        self.last_order_id().set(&order_id);
+
+        let order = Order {
+            owner: self.blockchain().get_caller(),
+            pair_address,
+            token_in,
+            amount_in,
+            token_out,
+            min_amount_out,
+            expiry,
+        };
+        self.emit_create_order_event(order_id, &order);
+        self.orders(order_id).set(&order);
+
+        Ok(order_id)
+    }
+
+    // Permissionless. The keeper receives its fee out of the amount swapped.
+    #[endpoint(fillOrder)]
+    fn fill_order(&self, order_id: u64) -> SCResult<()> {
+        require!(!self.orders(order_id).is_empty(), "Order not found");
+        let order = self.orders(order_id).get();
+        require!(
+            self.blockchain().get_block_timestamp() <= order.expiry,
+            "Order expired"
+        );
+
+        let amount_out: BigUint = self
+            .pair_contract_proxy(order.pair_address.clone())
+            .get_amount_out_view(order.token_in.clone(), order.amount_in.clone())
+            .execute_on_dest_context();
+        let expected_keeper_fee = &amount_out * self.keeper_fee().get() / MAX_KEEPER_FEE;
+        require!(
+            &amount_out - &expected_keeper_fee >= order.min_amount_out,
+            "Limit price not reached"
+        );
+
+        // The amount received is measured on the balance, so the keeper fee and the limit
+        // price are checked against what was actually swapped.
+        let balance_before = self.blockchain().get_sc_balance(&order.token_out, 0);
+        self.swap_fixed_input(&order, amount_out);
+        let amount_received =
+            self.blockchain().get_sc_balance(&order.token_out, 0) - balance_before;
+        let keeper_fee_amount = &amount_received * self.keeper_fee().get() / MAX_KEEPER_FEE;
+        let owner_amount = &amount_received - &keeper_fee_amount;
+        require!(
+            owner_amount >= order.min_amount_out,
+            "Limit price not reached"
+        );
+        self.orders(order_id).clear();
+
+        let keeper = self.blockchain().get_caller();
+        self.send()
+            .direct(&order.owner, &order.token_out, 0, &owner_amount, &[]);
+        if keeper_fee_amount > 0 {
+            self.send()
+                .direct(&keeper, &order.token_out, 0, &keeper_fee_amount, &[]);
+        }
+
+        self.emit_fill_order_event(order_id, &order, &keeper, &owner_amount, &keeper_fee_amount);
+        Ok(())
+    }
+
+    // Also the only way for the owner to get the funds of an expired order back.
+    #[endpoint(cancelOrder)]
+    fn cancel_order(&self, order_id: u64) -> SCResult<()> {
+        require!(!self.orders(order_id).is_empty(), "Order not found");
+        let order = self.orders(order_id).get();
+        require!(
+            self.blockchain().get_caller() == order.owner,
+            "Only the order owner can cancel it"
+        );
+        self.orders(order_id).clear();
+
+        self.send()
+            .direct(&order.owner, &order.token_in, 0, &order.amount_in, &[]);
+
+        self.emit_cancel_order_event(order_id, &order);
+        Ok(())
+    }
+
+    #[payable("*")]
+    #[endpoint(acceptPay)]
+    fn accept_pay(&self) {}
+
+    #[only_owner]
+    #[endpoint(setKeeperFee)]
+    fn set_keeper_fee(&self, keeper_fee: u64) -> SCResult<()> {
+        require!(keeper_fee < MAX_KEEPER_FEE, "Invalid keeper fee");
+        self.keeper_fee().set(&keeper_fee);
+        Ok(())
+    }
+
+    fn get_pair_address(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        opt_fee_tier: OptionalArg<u64>,
+    ) -> SCResult<ManagedAddress> {
+        let pair_address: ManagedAddress = self
+            .router_contract_proxy(self.router_address().get())
+            .get_pair(token_in.clone(), token_out.clone(), opt_fee_tier)
+            .execute_on_dest_context();
+        require!(!pair_address.is_zero(), "Pair not found");
+
+        let first_token_id: TokenIdentifier = self
+            .pair_contract_proxy(pair_address.clone())
+            .first_token_id()
+            .execute_on_dest_context();
+        let second_token_id: TokenIdentifier = self
+            .pair_contract_proxy(pair_address.clone())
+            .second_token_id()
+            .execute_on_dest_context();
+        require!(
+            (&first_token_id == token_in && &second_token_id == token_out)
+                || (&first_token_id == token_out && &second_token_id == token_in),
+            "Invalid pair tokens"
+        );
+        Ok(pair_address)
+    }
+
+    fn swap_fixed_input(&self, order: &Order<Self::Api>, amount_out_min: BigUint) {
+        self.pair_contract_proxy(order.pair_address.clone())
+            .swap_tokens_fixed_input(
+                order.token_in.clone(),
+                order.amount_in.clone(),
+                order.token_out.clone(),
+                amount_out_min,
+                OptionalArg::Some(order.expiry),
+                OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
+            )
+            .execute_on_dest_context_ignore_result();
+    }
+
+    #[proxy]
+    fn router_contract_proxy(&self, to: ManagedAddress) -> router::Proxy<Self::Api>;
+
+    #[proxy]
+    fn pair_contract_proxy(&self, to: ManagedAddress) -> pair::Proxy<Self::Api>;
+
+    #[view(getRouterAddress)]
+    #[storage_mapper("router_address")]
+    fn router_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getOrder)]
+    #[storage_mapper("orders")]
+    fn orders(&self, order_id: u64) -> SingleValueMapper<Order<Self::Api>>;
+
+    #[view(getLastOrderId)]
+    #[storage_mapper("last_order_id")]
+    fn last_order_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getKeeperFee)]
+    #[storage_mapper("keeper_fee")]
+    fn keeper_fee(&self) -> SingleValueMapper<u64>;
+}
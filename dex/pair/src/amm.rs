@@ -3,6 +3,15 @@ elrond_wasm::derive_imports!();
 
 use super::config;
 
+type Nonce = u64;
+
+const N_COINS: u64 = 2;
+const STABLESWAP_MAX_ITERATIONS: usize = 255;
+pub const MAX_AMP_FACTOR: u64 = 1_000_000;
+const MAX_AMP_FACTOR_CHANGE: u64 = 10;
+const MIN_AMP_RAMP_BLOCKS: u64 = 14_400;
+const WEIGHT_PRECISION: u64 = 1_000_000_000_000_000_000;
+
 #[elrond_wasm::module]
 pub trait AmmModule: config::ConfigModule + token_send::TokenSendModule {
     fn calculate_k_constant(
@@ -10,7 +19,12 @@ pub trait AmmModule: config::ConfigModule + token_send::TokenSendModule {
         first_token_amount: &BigUint,
         second_token_amount: &BigUint,
     ) -> BigUint {
-        first_token_amount * second_token_amount
+        if self.is_stableswap() {
+            let amp_factor = self.get_amp_factor();
+            self.compute_stableswap_d(first_token_amount, second_token_amount, amp_factor)
+        } else {
+            first_token_amount * second_token_amount
+        }
     }
 
     fn quote(
@@ -28,6 +42,10 @@ pub trait AmmModule: config::ConfigModule + token_send::TokenSendModule {
         reserve_in: &BigUint,
         reserve_out: &BigUint,
     ) -> BigUint {
+        if self.is_stableswap() {
+            return self.get_stableswap_amount_out(amount_in, reserve_in, reserve_out);
+        }
+
         let numerator = amount_in * reserve_out;
         let denominator = reserve_in + amount_in;
 
@@ -41,6 +59,11 @@ pub trait AmmModule: config::ConfigModule + token_send::TokenSendModule {
         reserve_out: &BigUint,
     ) -> BigUint {
         let amount_in_with_fee = amount_in * (100000 - self.total_fee_percent().get());
+        if self.is_stableswap() {
+            let amount_in_after_fee = amount_in_with_fee / 100000u64;
+            return self.get_stableswap_amount_out(&amount_in_after_fee, reserve_in, reserve_out);
+        }
+
         let numerator = &amount_in_with_fee * reserve_out;
         let denominator = (reserve_in * 100000u64) + amount_in_with_fee;
 
@@ -53,13 +76,402 @@ pub trait AmmModule: config::ConfigModule + token_send::TokenSendModule {
         reserve_in: &BigUint,
         reserve_out: &BigUint,
     ) -> BigUint {
+        if self.is_stableswap() {
+            let amount_in_after_fee =
+                self.get_stableswap_amount_in(amount_out, reserve_in, reserve_out);
+            let numerator = amount_in_after_fee * 100000u64;
+            let denominator = 100000 - self.total_fee_percent().get();
+
+            return (numerator / denominator) + 1u64;
+        }
+
         let numerator = reserve_in * amount_out * 100000u64;
         let denominator = (reserve_out - amount_out) * (100000 - self.total_fee_percent().get());
 
         (numerator / denominator) + 1u64
     }
 
+    // Part of a single token deposit that has to be swapped so that the rest of the deposit and
+    // the swap output match the ratio of the reserves, for the constant product curve.
+    // With F = 100000 and g = F - total_fee:
+    // s = (sqrt(r^2 * (F + g)^2 + 4 * F * g * a * r) - r * (F + g)) / (2 * g)
+    fn get_optimal_swap_amount(&self, amount_in: &BigUint, reserve_in: &BigUint) -> BigUint {
+        let fee_denominator = 100000u64;
+        let amount_after_fee = fee_denominator - self.total_fee_percent().get();
+
+        let reserve_term = reserve_in * (fee_denominator + amount_after_fee);
+        let discriminant = &reserve_term * &reserve_term
+            + amount_in * reserve_in * (4 * fee_denominator * amount_after_fee);
+        // sqrt(A^2 + B) <= A + B / 2A, which is lower than the guess below.
+        let initial_guess = &reserve_term + &(amount_in * (2 * fee_denominator)) + 1u64;
+        let root = self.nth_root(&discriminant, 2, initial_guess);
+
+        (root - reserve_term) / (2 * amount_after_fee)
+    }
+
     fn get_special_fee_from_input(&self, amount_in: &BigUint) -> BigUint {
         amount_in * self.special_fee_percent().get() / 100000u64
     }
+
+    // Stableswap (Curve style) invariant for two tokens:
+    // A * n^n * (x + y) + D = A * D * n^n + D^(n + 1) / (n^n * x * y)
+    // The amounts are assumed to have the same precision.
+
+    #[inline]
+    fn is_stableswap(&self) -> bool {
+        self.future_amp_factor().get() != 0
+    }
+
+    fn get_stableswap_amount_out(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        let amp_factor = self.get_amp_factor();
+        let d = self.compute_stableswap_d(reserve_in, reserve_out, amp_factor);
+        let new_reserve_in = reserve_in + amount_in;
+        let new_reserve_out = self.compute_stableswap_y(&new_reserve_in, &d, amp_factor);
+
+        // One unit is kept in the pool to cover the rounding of the newton iterations.
+        let min_reserve_out = &new_reserve_out + 1u64;
+        if reserve_out <= &min_reserve_out {
+            return BigUint::zero();
+        }
+        reserve_out - &min_reserve_out
+    }
+
+    fn get_stableswap_amount_in(
+        &self,
+        amount_out: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        let amp_factor = self.get_amp_factor();
+        let d = self.compute_stableswap_d(reserve_in, reserve_out, amp_factor);
+        let new_reserve_out = reserve_out - amount_out;
+        let new_reserve_in = self.compute_stableswap_y(&new_reserve_out, &d, amp_factor);
+
+        new_reserve_in - reserve_in + 1u64
+    }
+
+    // Marginal price of the input token, from the partial derivatives of the invariant.
+    // With x = reserve_in, y = reserve_out and ann = A * n:
+    // price = y * (4 * ann * x^2 * y + D^3) / (x * (4 * ann * x * y^2 + D^3))
+    fn get_stableswap_spot_price(
+        &self,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        precision: u64,
+    ) -> BigUint {
+        let amp_factor = self.get_amp_factor();
+        let d = self.compute_stableswap_d(reserve_in, reserve_out, amp_factor);
+        let d_cubed = &d * &d * &d;
+        let ann_term = reserve_in * reserve_out * (4 * amp_factor * N_COINS);
+
+        let numerator = reserve_out * &(&ann_term * reserve_in + &d_cubed) * precision;
+        let denominator = reserve_in * &(&ann_term * reserve_out + &d_cubed);
+        numerator / denominator
+    }
+
+    fn compute_stableswap_d(
+        &self,
+        first_token_amount: &BigUint,
+        second_token_amount: &BigUint,
+        amp_factor: u64,
+    ) -> BigUint {
+        let sum = first_token_amount + second_token_amount;
+        if sum == 0 || first_token_amount == &0 || second_token_amount == &0 {
+            return BigUint::zero();
+        }
+
+        let ann = BigUint::from(amp_factor * N_COINS);
+        let mut d = sum.clone();
+        for _ in 0..STABLESWAP_MAX_ITERATIONS {
+            let mut d_product = d.clone();
+            d_product = &d_product * &d / (first_token_amount * N_COINS);
+            d_product = &d_product * &d / (second_token_amount * N_COINS);
+
+            let d_prev = d.clone();
+            let numerator = (&ann * &sum + &d_product * N_COINS) * &d;
+            let denominator = (&ann - &BigUint::from(1u64)) * &d + &d_product * (N_COINS + 1);
+            d = numerator / denominator;
+
+            if self.is_converged(&d, &d_prev) {
+                break;
+            }
+        }
+
+        d
+    }
+
+    fn compute_stableswap_y(
+        &self,
+        known_amount: &BigUint,
+        d: &BigUint,
+        amp_factor: u64,
+    ) -> BigUint {
+        let ann = BigUint::from(amp_factor * N_COINS);
+
+        let mut c = d * d / (known_amount * N_COINS);
+        c = c * d / (&ann * N_COINS);
+        let b = known_amount + &(d / &ann);
+
+        let mut y = d.clone();
+        for _ in 0..STABLESWAP_MAX_ITERATIONS {
+            let y_prev = y.clone();
+            let numerator = &y * &y + &c;
+            let denominator = &y * 2u64 + &b - d;
+            y = numerator / denominator;
+
+            if self.is_converged(&y, &y_prev) {
+                break;
+            }
+        }
+
+        y
+    }
+
+    fn is_converged(&self, current: &BigUint, previous: &BigUint) -> bool {
+        if current > previous {
+            current - previous <= 1
+        } else {
+            previous - current <= 1
+        }
+    }
+
+    fn try_init_amp_factor(&self, amp_factor: u64) -> SCResult<()> {
+        require!(!self.is_stableswap(), "Amp factor already set");
+        require!(
+            amp_factor > 0 && amp_factor <= MAX_AMP_FACTOR,
+            "Bad amp factor"
+        );
+
+        let current_block = self.blockchain().get_block_nonce();
+        self.initial_amp_factor().set(&amp_factor);
+        self.future_amp_factor().set(&amp_factor);
+        self.initial_amp_factor_block().set(&current_block);
+        self.future_amp_factor_block().set(&current_block);
+        Ok(())
+    }
+
+    // Weighted (Balancer style) invariant for two tokens: x^wx * y^wy = k.
+    // The weights are reduced by their greatest common divisor before being used as exponents.
+
+    fn get_weighted_amount_out(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        weight_in: u64,
+        weight_out: u64,
+    ) -> BigUint {
+        let amount_in_after_fee = amount_in * (100000 - self.total_fee_percent().get()) / 100000u64;
+        self.get_weighted_amount_out_no_fee(
+            &amount_in_after_fee,
+            reserve_in,
+            reserve_out,
+            weight_in,
+            weight_out,
+        )
+    }
+
+    fn get_weighted_amount_out_no_fee(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        weight_in: u64,
+        weight_out: u64,
+    ) -> BigUint {
+        let precision = BigUint::from(WEIGHT_PRECISION);
+        let new_reserve_in = reserve_in + amount_in;
+        let ratio = self.div_ceil(&(reserve_in * &precision), &new_reserve_in);
+        let ratio_pow = self.pow_ratio_up(&ratio, weight_in, weight_out);
+        if ratio_pow >= precision {
+            return BigUint::zero();
+        }
+
+        reserve_out * &(&precision - &ratio_pow) / precision
+    }
+
+    fn get_weighted_amount_in(
+        &self,
+        amount_out: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        weight_in: u64,
+        weight_out: u64,
+    ) -> BigUint {
+        let precision = BigUint::from(WEIGHT_PRECISION);
+        let new_reserve_out = reserve_out - amount_out;
+        let ratio = self.div_ceil(&(reserve_out * &precision), &new_reserve_out);
+        let ratio_pow = self.pow_ratio_up(&ratio, weight_out, weight_in);
+        let amount_in_after_fee =
+            self.div_ceil(&(reserve_in * &(ratio_pow - &precision)), &precision);
+
+        let numerator = amount_in_after_fee * 100000u64;
+        let denominator = 100000 - self.total_fee_percent().get();
+        (numerator / denominator) + 1u64
+    }
+
+    fn calculate_weighted_k_constant(
+        &self,
+        first_token_amount: &BigUint,
+        second_token_amount: &BigUint,
+        first_token_weight: u64,
+        second_token_weight: u64,
+    ) -> BigUint {
+        let (first_exp, second_exp) = self.reduce_weights(first_token_weight, second_token_weight);
+        self.big_pow(first_token_amount, first_exp) * self.big_pow(second_token_amount, second_exp)
+    }
+
+    // Computes precision * (ratio / precision) ^ (weight_num / weight_den), rounded up.
+    fn pow_ratio_up(&self, ratio: &BigUint, weight_num: u64, weight_den: u64) -> BigUint {
+        let precision = BigUint::from(WEIGHT_PRECISION);
+        let (exp_num, exp_den) = self.reduce_weights(weight_num, weight_den);
+
+        let numerator = self.big_pow(ratio, exp_num) * self.big_pow(&precision, exp_den);
+        let denominator = self.big_pow(&precision, exp_num);
+        let value = self.div_ceil(&numerator, &denominator);
+        if exp_den == 1 {
+            return value;
+        }
+
+        // The newton iterations need a starting point greater than the root.
+        let initial_guess = if ratio <= &precision {
+            precision
+        } else if exp_num <= exp_den {
+            ratio.clone()
+        } else {
+            self.big_pow(ratio, exp_num) / self.big_pow(&precision, exp_num - 1) + 1u64
+        };
+
+        let root = self.nth_root(&value, exp_den, initial_guess);
+        if self.big_pow(&root, exp_den) < value {
+            root + 1u64
+        } else {
+            root
+        }
+    }
+
+    fn nth_root(&self, value: &BigUint, n: u64, initial_guess: BigUint) -> BigUint {
+        if value == &0 {
+            return BigUint::zero();
+        }
+
+        let mut x = initial_guess;
+        loop {
+            let y = (&x * (n - 1) + value / &self.big_pow(&x, n - 1)) / n;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    fn big_pow(&self, base: &BigUint, exp: u64) -> BigUint {
+        let mut result = BigUint::from(1u64);
+        for _ in 0..exp {
+            result *= base;
+        }
+        result
+    }
+
+    fn div_ceil(&self, numerator: &BigUint, denominator: &BigUint) -> BigUint {
+        (numerator + denominator - BigUint::from(1u64)) / denominator
+    }
+
+    fn reduce_weights(&self, first_weight: u64, second_weight: u64) -> (u64, u64) {
+        let mut a = first_weight;
+        let mut b = second_weight;
+        while b != 0 {
+            let remainder = a % b;
+            a = b;
+            b = remainder;
+        }
+        (first_weight / a, second_weight / a)
+    }
+
+    #[endpoint(rampAmpFactor)]
+    fn ramp_amp_factor(&self, future_amp_factor: u64, ramp_blocks: u64) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(self.is_stableswap(), "Not a stableswap pair");
+        require!(
+            future_amp_factor > 0 && future_amp_factor <= MAX_AMP_FACTOR,
+            "Bad amp factor"
+        );
+        require!(ramp_blocks >= MIN_AMP_RAMP_BLOCKS, "Ramp period too short");
+
+        let current_block = self.blockchain().get_block_nonce();
+        require!(
+            current_block >= self.future_amp_factor_block().get(),
+            "Amp factor ramp in progress"
+        );
+
+        let current_amp_factor = self.get_amp_factor();
+        require!(
+            future_amp_factor <= current_amp_factor * MAX_AMP_FACTOR_CHANGE
+                && future_amp_factor * MAX_AMP_FACTOR_CHANGE >= current_amp_factor,
+            "Amp factor change too big"
+        );
+
+        self.initial_amp_factor().set(&current_amp_factor);
+        self.future_amp_factor().set(&future_amp_factor);
+        self.initial_amp_factor_block().set(&current_block);
+        self.future_amp_factor_block()
+            .set(&(current_block + ramp_blocks));
+        Ok(())
+    }
+
+    #[endpoint(stopRampAmpFactor)]
+    fn stop_ramp_amp_factor(&self) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(self.is_stableswap(), "Not a stableswap pair");
+
+        let current_block = self.blockchain().get_block_nonce();
+        let current_amp_factor = self.get_amp_factor();
+        self.initial_amp_factor().set(&current_amp_factor);
+        self.future_amp_factor().set(&current_amp_factor);
+        self.initial_amp_factor_block().set(&current_block);
+        self.future_amp_factor_block().set(&current_block);
+        Ok(())
+    }
+
+    #[view(getAmpFactor)]
+    fn get_amp_factor(&self) -> u64 {
+        let future_amp_factor = self.future_amp_factor().get();
+        let future_block = self.future_amp_factor_block().get();
+        let current_block = self.blockchain().get_block_nonce();
+        if current_block >= future_block {
+            return future_amp_factor;
+        }
+
+        let initial_amp_factor = self.initial_amp_factor().get();
+        let initial_block = self.initial_amp_factor_block().get();
+        let elapsed = current_block - initial_block;
+        let duration = future_block - initial_block;
+
+        if future_amp_factor > initial_amp_factor {
+            initial_amp_factor + (future_amp_factor - initial_amp_factor) * elapsed / duration
+        } else {
+            initial_amp_factor - (initial_amp_factor - future_amp_factor) * elapsed / duration
+        }
+    }
+
+    #[view(getInitialAmpFactor)]
+    #[storage_mapper("initial_amp_factor")]
+    fn initial_amp_factor(&self) -> SingleValueMapper<u64>;
+
+    #[view(getFutureAmpFactor)]
+    #[storage_mapper("future_amp_factor")]
+    fn future_amp_factor(&self) -> SingleValueMapper<u64>;
+
+    #[view(getInitialAmpFactorBlock)]
+    #[storage_mapper("initial_amp_factor_block")]
+    fn initial_amp_factor_block(&self) -> SingleValueMapper<Nonce>;
+
+    #[view(getFutureAmpFactorBlock)]
+    #[storage_mapper("future_amp_factor_block")]
+    fn future_amp_factor_block(&self) -> SingleValueMapper<Nonce>;
 }
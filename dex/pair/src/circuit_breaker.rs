@@ -0,0 +1,157 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::amm;
+use super::config;
+use super::events;
+use super::fee;
+use super::liquidity_pool;
+use super::oracle;
+use super::quote;
+
+use config::State;
+
+const MAX_BASIS_POINTS: u64 = 10_000;
+
+type Nonce = u64;
+
+// Prices are the first token price, with the precision of the oracle records.
+// Both thresholds are in basis points and a zero value disables the check.
+#[elrond_wasm::module]
+pub trait CircuitBreakerModule:
+    amm::AmmModule
+    + config::ConfigModule
+    + events::EventsModule
+    + fee::FeeModule
+    + liquidity_pool::LiquidityPoolModule
+    + oracle::OracleModule
+    + quote::QuoteModule
+    + token_supply::TokenSupplyModule
+    + token_send::TokenSendModule
+{
+    #[endpoint(setCircuitBreaker)]
+    fn set_circuit_breaker(
+        &self,
+        max_twap_deviation: u64,
+        max_block_price_movement: u64,
+        twap_blocks: u64,
+    ) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(
+            max_twap_deviation <= MAX_BASIS_POINTS,
+            "Invalid max TWAP deviation"
+        );
+        require!(
+            max_block_price_movement <= MAX_BASIS_POINTS,
+            "Invalid max block price movement"
+        );
+        require!(
+            max_twap_deviation == 0 || twap_blocks != 0,
+            "Invalid TWAP blocks"
+        );
+
+        self.max_twap_deviation().set(&max_twap_deviation);
+        self.max_block_price_movement()
+            .set(&max_block_price_movement);
+        self.circuit_breaker_twap_blocks().set(&twap_blocks);
+        self.block_reference_price().clear();
+        self.block_reference_price_block().clear();
+        Ok(())
+    }
+
+    // Same spot price as the quotes, which follows the curve of stableswap pairs.
+    fn get_first_token_spot_price(&self) -> BigUint {
+        let first_token_id = self.first_token_id().get();
+        let first_token_reserve = self.pair_reserve(&first_token_id).get();
+        let second_token_reserve = self.pair_reserve(&self.second_token_id().get()).get();
+        self.get_spot_price(&first_token_id, &first_token_reserve, &second_token_reserve)
+    }
+
+    // The price before the first swap of a block is the reference for the whole block.
+    fn record_block_reference_price(&self) {
+        if self.max_block_price_movement().get() == 0 {
+            return;
+        }
+
+        let current_block = self.blockchain().get_block_nonce();
+        if !self.block_reference_price_block().is_empty()
+            && self.block_reference_price_block().get() == current_block
+        {
+            return;
+        }
+
+        self.block_reference_price()
+            .set(&self.get_first_token_spot_price());
+        self.block_reference_price_block().set(&current_block);
+    }
+
+    // A swap pushing the price too far from the TWAP is rejected. A swap pushing the price
+    // too far from the block reference is executed, but swaps are disabled after it.
+    // An inactive pair is left inactive.
+    fn check_circuit_breaker(&self, caller: &ManagedAddress) -> SCResult<()> {
+        let max_twap_deviation = self.max_twap_deviation().get();
+        let max_block_price_movement = self.max_block_price_movement().get();
+        if max_twap_deviation == 0 && max_block_price_movement == 0 {
+            return Ok(());
+        }
+
+        let price = self.get_first_token_spot_price();
+        if max_twap_deviation != 0 {
+            let twap_blocks = self.circuit_breaker_twap_blocks().get();
+            if let SCResult::Ok(twap) = self.get_twap_for_last_blocks(twap_blocks) {
+                let (first_token_twap, _) = twap.into_tuple();
+                require!(
+                    self.get_deviation_basis_points(&first_token_twap, &price)
+                        <= max_twap_deviation,
+                    "Price deviates too much from TWAP"
+                );
+            }
+        }
+
+        if max_block_price_movement != 0 {
+            let reference_price = self.block_reference_price().get();
+            if self.get_deviation_basis_points(&reference_price, &price) > max_block_price_movement
+                && self.state().get() == State::Active
+            {
+                self.state().set(&State::ActiveNoSwaps);
+                self.emit_circuit_breaker_event(caller, &reference_price, &price);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_deviation_basis_points(&self, reference: &BigUint, price: &BigUint) -> u64 {
+        if reference == &0 {
+            return 0;
+        }
+
+        let difference = if price > reference {
+            price - reference
+        } else {
+            reference - price
+        };
+        let deviation = difference * MAX_BASIS_POINTS / reference;
+        deviation.to_u64().unwrap_or(u64::MAX)
+    }
+
+    #[view(getMaxTwapDeviation)]
+    #[storage_mapper("max_twap_deviation")]
+    fn max_twap_deviation(&self) -> SingleValueMapper<u64>;
+
+    #[view(getMaxBlockPriceMovement)]
+    #[storage_mapper("max_block_price_movement")]
+    fn max_block_price_movement(&self) -> SingleValueMapper<u64>;
+
+    #[view(getCircuitBreakerTwapBlocks)]
+    #[storage_mapper("circuit_breaker_twap_blocks")]
+    fn circuit_breaker_twap_blocks(&self) -> SingleValueMapper<u64>;
+
+    #[view(getBlockReferencePrice)]
+    #[storage_mapper("block_reference_price")]
+    fn block_reference_price(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getBlockReferencePriceBlock)]
+    #[storage_mapper("block_reference_price_block")]
+    fn block_reference_price_block(&self) -> SingleValueMapper<Nonce>;
+}
@@ -0,0 +1,123 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::amm;
+use super::config;
+use super::liquidity_pool;
+
+pub mod egld_wrapper_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait EgldWrapper {
+        #[payable("EGLD")]
+        #[endpoint(wrapEgld)]
+        fn wrap_egld(
+            &self,
+            #[payment_amount] payment_amount: BigUint,
+        ) -> EsdtTokenPayment<Self::Api>;
+
+        #[payable("*")]
+        #[endpoint(unwrapEgld)]
+        fn unwrap_egld(
+            &self,
+            #[payment_token] payment_token: TokenIdentifier,
+            #[payment_amount] payment_amount: BigUint,
+        );
+
+        #[view(getWrappedEgldTokenId)]
+        fn get_wrapped_egld_token_id(&self) -> TokenIdentifier;
+    }
+}
+
+#[elrond_wasm::module]
+pub trait EgldWrapperModule:
+    amm::AmmModule
+    + config::ConfigModule
+    + liquidity_pool::LiquidityPoolModule
+    + token_supply::TokenSupplyModule
+    + token_send::TokenSendModule
+{
+    #[proxy]
+    fn egld_wrapper_proxy(&self, to: ManagedAddress) -> egld_wrapper_proxy::Proxy<Self::Api>;
+
+    #[endpoint(setEgldWrapperAddress)]
+    fn set_egld_wrapper_address(&self, address: ManagedAddress) -> SCResult<()> {
+        self.require_permissions()?;
+        let wrapped_egld_token_id: TokenIdentifier = self
+            .egld_wrapper_proxy(address.clone())
+            .get_wrapped_egld_token_id()
+            .execute_on_dest_context();
+        require!(
+            wrapped_egld_token_id == self.first_token_id().get()
+                || wrapped_egld_token_id == self.second_token_id().get(),
+            "Wrapped EGLD is not a pair token"
+        );
+
+        self.egld_wrapper_address().set(&address);
+        self.wrapped_egld_token_id().set(&wrapped_egld_token_id);
+        Ok(())
+    }
+
+    // EGLD stands for the wrapped EGLD side of the pair.
+    fn resolve_egld_token_id(&self, token_id: &TokenIdentifier) -> SCResult<TokenIdentifier> {
+        if !token_id.is_egld() {
+            return Ok(token_id.clone());
+        }
+
+        require!(
+            !self.egld_wrapper_address().is_empty(),
+            "EGLD wrapper not set"
+        );
+        Ok(self.wrapped_egld_token_id().get())
+    }
+
+    fn wrap_egld(&self, amount: &BigUint) -> EsdtTokenPayment<Self::Api> {
+        self.egld_wrapper_proxy(self.egld_wrapper_address().get())
+            .wrap_egld(amount.clone())
+            .execute_on_dest_context()
+    }
+
+    fn unwrap_egld(&self, amount: &BigUint) {
+        self.egld_wrapper_proxy(self.egld_wrapper_address().get())
+            .unwrap_egld(self.wrapped_egld_token_id().get(), amount.clone())
+            .execute_on_dest_context();
+    }
+
+    // Same as transfer_execute_custom, but sends EGLD out of wrapped EGLD when EGLD is requested.
+    fn transfer_token_or_egld(
+        &self,
+        to: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        amount: &BigUint,
+        opt_accept_funds_func: &OptionalArg<BoxedBytes>,
+    ) -> SCResult<()> {
+        if token_id.is_egld() {
+            self.transfer_egld_from_wrapped(to, amount, opt_accept_funds_func)
+        } else {
+            self.transfer_execute_custom(to, token_id, 0, amount, opt_accept_funds_func)
+        }
+    }
+
+    fn transfer_egld_from_wrapped(
+        &self,
+        to: &ManagedAddress,
+        amount: &BigUint,
+        opt_accept_funds_func: &OptionalArg<BoxedBytes>,
+    ) -> SCResult<()> {
+        if amount == &0 {
+            return Ok(());
+        }
+
+        self.unwrap_egld(amount);
+        self.transfer_egld_execute_custom(to, amount, opt_accept_funds_func)
+    }
+
+    #[view(getEgldWrapperAddress)]
+    #[storage_mapper("egld_wrapper_address")]
+    fn egld_wrapper_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getWrappedEgldTokenId)]
+    #[storage_mapper("wrapped_egld_token_id")]
+    fn wrapped_egld_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+}
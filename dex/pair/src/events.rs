@@ -16,6 +16,57 @@ pub struct SwapEvent<M: ManagedTypeApi> {
     timestamp: u64,
 }
 
+#[derive(TopEncode)]
+pub struct FlashSwapEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    receiver: ManagedAddress<M>,
+    token_id_out: TokenIdentifier<M>,
+    token_amount_out: BigUint<M>,
+    token_id_in: TokenIdentifier<M>,
+    token_amount_in_repaid: BigUint<M>,
+    token_amount_out_repaid: BigUint<M>,
+    token_in_reserve: BigUint<M>,
+    token_out_reserve: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct CircuitBreakerEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    reference_price: BigUint<M>,
+    price: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct SyncEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    first_token_id: TokenIdentifier<M>,
+    first_token_reserve: BigUint<M>,
+    second_token_id: TokenIdentifier<M>,
+    second_token_reserve: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct SkimEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    destination: ManagedAddress<M>,
+    first_token_id: TokenIdentifier<M>,
+    first_token_amount: BigUint<M>,
+    second_token_id: TokenIdentifier<M>,
+    second_token_amount: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[derive(TopEncode)]
 pub struct SwapNoFeeAndForwardEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
@@ -98,6 +149,115 @@ pub trait EventsModule {
         )
     }
 
+    fn emit_flash_swap_event(
+        &self,
+        caller: &ManagedAddress,
+        receiver: &ManagedAddress,
+        token_id_out: &TokenIdentifier,
+        token_amount_out: &BigUint,
+        token_id_in: &TokenIdentifier,
+        token_amount_in_repaid: &BigUint,
+        token_amount_out_repaid: &BigUint,
+        token_in_reserve: &BigUint,
+        token_out_reserve: &BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.flash_swap_event(
+            token_id_out,
+            caller,
+            receiver,
+            epoch,
+            &FlashSwapEvent {
+                caller: caller.clone(),
+                receiver: receiver.clone(),
+                token_id_out: token_id_out.clone(),
+                token_amount_out: token_amount_out.clone(),
+                token_id_in: token_id_in.clone(),
+                token_amount_in_repaid: token_amount_in_repaid.clone(),
+                token_amount_out_repaid: token_amount_out_repaid.clone(),
+                token_in_reserve: token_in_reserve.clone(),
+                token_out_reserve: token_out_reserve.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_circuit_breaker_event(
+        &self,
+        caller: &ManagedAddress,
+        reference_price: &BigUint,
+        price: &BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.circuit_breaker_event(
+            caller,
+            epoch,
+            &CircuitBreakerEvent {
+                caller: caller.clone(),
+                reference_price: reference_price.clone(),
+                price: price.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_sync_event(
+        &self,
+        caller: &ManagedAddress,
+        first_token_id: &TokenIdentifier,
+        first_token_reserve: &BigUint,
+        second_token_id: &TokenIdentifier,
+        second_token_reserve: &BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.sync_event(
+            caller,
+            epoch,
+            &SyncEvent {
+                caller: caller.clone(),
+                first_token_id: first_token_id.clone(),
+                first_token_reserve: first_token_reserve.clone(),
+                second_token_id: second_token_id.clone(),
+                second_token_reserve: second_token_reserve.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_skim_event(
+        &self,
+        caller: &ManagedAddress,
+        destination: &ManagedAddress,
+        first_token_id: &TokenIdentifier,
+        first_token_amount: &BigUint,
+        second_token_id: &TokenIdentifier,
+        second_token_amount: &BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.skim_event(
+            caller,
+            destination,
+            epoch,
+            &SkimEvent {
+                caller: caller.clone(),
+                destination: destination.clone(),
+                first_token_id: first_token_id.clone(),
+                first_token_amount: first_token_amount.clone(),
+                second_token_id: second_token_id.clone(),
+                second_token_amount: second_token_amount.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     fn emit_swap_no_fee_and_forward_event(
         &self,
         caller: &ManagedAddress,
@@ -210,6 +370,41 @@ pub trait EventsModule {
         swap_event: &SwapEvent<Self::Api>,
     );
 
+    #[event("flash_swap")]
+    fn flash_swap_event(
+        &self,
+        #[indexed] token_out: &TokenIdentifier,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] receiver: &ManagedAddress,
+        #[indexed] epoch: u64,
+        flash_swap_event: &FlashSwapEvent<Self::Api>,
+    );
+
+    #[event("circuit_breaker")]
+    fn circuit_breaker_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] epoch: u64,
+        circuit_breaker_event: &CircuitBreakerEvent<Self::Api>,
+    );
+
+    #[event("sync")]
+    fn sync_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] epoch: u64,
+        sync_event: &SyncEvent<Self::Api>,
+    );
+
+    #[event("skim")]
+    fn skim_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] destination: &ManagedAddress,
+        #[indexed] epoch: u64,
+        skim_event: &SkimEvent<Self::Api>,
+    );
+
     #[event("swap_no_fee_and_forward")]
     fn swap_no_fee_and_forward_event(
         &self,
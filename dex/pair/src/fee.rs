@@ -8,6 +8,15 @@ use common_structs::TokenPair;
 use core::iter::FromIterator;
 
 const SWAP_NO_FEE_AND_FORWARD_FUNC_NAME: &[u8] = b"swapNoFeeAndForward";
+const MAX_FEE_SHARE: u64 = 10_000;
+const DEFAULT_FEE_DISTRIBUTION_SWAPS: u64 = 100;
+
+#[derive(TopEncode, TopDecode, PartialEq, TypeAbi)]
+pub enum FeeMode {
+    Direct,
+    LiquidityMint,
+    Deferred,
+}
 
 mod farm_proxy {
     elrond_wasm::imports!();
@@ -109,6 +118,10 @@ pub trait FeeModule:
     }
 
     fn reinject(&self, token: &TokenIdentifier, amount: &BigUint) {
+        if amount == &0 {
+            return;
+        }
+
         self.pair_reserve(token)
             .update(|reserve| *reserve += amount);
         self.pair_virtual_reserve(token, token)
@@ -120,23 +133,101 @@ pub trait FeeModule:
             return;
         }
 
-        let slices = self.destination_map().len() as u64;
-        if slices == 0 {
-            self.reinject(fee_token, fee_amount);
-            return;
+        match self.fee_mode().get() {
+            FeeMode::Direct => {
+                let fee_not_sent = self.distribute_fee(fee_token, fee_amount);
+                self.reinject(fee_token, &fee_not_sent);
+            }
+            // The protocol share stays in the pool and is later taken by mint_fee.
+            FeeMode::LiquidityMint => self.reinject(fee_token, fee_amount),
+            FeeMode::Deferred => {
+                self.pending_fees(fee_token)
+                    .update(|pending| *pending += fee_amount);
+                self.swaps_since_fee_distribution()
+                    .update(|swaps| *swaps += 1);
+            }
+        }
+    }
+
+    // Sends the fees accumulated in the Deferred fee mode. Anyone can call it once every
+    // fee distribution swaps or once per epoch. Slices that cannot be sent stay pending.
+    #[endpoint(distributeFees)]
+    fn distribute_fees(&self) -> SCResult<()> {
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        require!(
+            self.pending_fees(&first_token_id).get() > 0
+                || self.pending_fees(&second_token_id).get() > 0,
+            "No pending fees"
+        );
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(
+            self.swaps_since_fee_distribution().get() >= self.get_fee_distribution_swaps()
+                || current_epoch > self.last_fee_distribution_epoch().get(),
+            "Fee distribution is not due yet"
+        );
+
+        for token_id in [&first_token_id, &second_token_id].iter() {
+            let pending_fees = self.pending_fees(token_id).get();
+            let fee_not_sent = self.distribute_fee(token_id, &pending_fees);
+            self.pending_fees(token_id).set(&fee_not_sent);
+        }
+
+        self.swaps_since_fee_distribution().clear();
+        self.last_fee_distribution_epoch().set(&current_epoch);
+        Ok(())
+    }
+
+    #[view(getFeeDistributionSwaps)]
+    fn get_fee_distribution_swaps(&self) -> u64 {
+        if self.fee_distribution_swaps().is_empty() {
+            DEFAULT_FEE_DISTRIBUTION_SWAPS
+        } else {
+            self.fee_distribution_swaps().get()
+        }
+    }
+
+    #[endpoint(setFeeDistributionSwaps)]
+    fn set_fee_distribution_swaps(&self, swaps: u64) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(swaps != 0, "Invalid number of swaps");
+
+        self.fee_distribution_swaps().set(&swaps);
+        Ok(())
+    }
+
+    // The fee is split by the destination shares when they are set, otherwise in equal
+    // slices. The rounding error is reinjected, while the amount of the slices that could
+    // not be sent is returned.
+    fn distribute_fee(&self, fee_token: &TokenIdentifier, fee_amount: &BigUint) -> BigUint {
+        if fee_amount == &0 {
+            return BigUint::zero();
         }
 
-        let fee_slice = fee_amount / slices;
-        if fee_slice == 0 {
+        let slices = self.destination_map().len() as u64;
+        if slices == 0 {
             self.reinject(fee_token, fee_amount);
-            return;
+            return BigUint::zero();
         }
 
+        let is_split_by_shares = self.get_total_fee_share() != 0;
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
+        let mut fee_sent = BigUint::zero();
+        let mut fee_not_sent = BigUint::zero();
 
         for (fee_address, fee_token_requested) in self.destination_map().iter() {
-            self.send_fee_slice(
+            let fee_slice = if is_split_by_shares {
+                fee_amount * self.fee_destination_share(&fee_address).get() / MAX_FEE_SHARE
+            } else {
+                fee_amount / slices
+            };
+            if fee_slice == 0 {
+                continue;
+            }
+
+            let is_sent = self.send_fee_slice(
                 fee_token,
                 &fee_slice,
                 &fee_address,
@@ -144,14 +235,28 @@ pub trait FeeModule:
                 &first_token_id,
                 &second_token_id,
             );
+            if is_sent {
+                fee_sent += &fee_slice;
+            } else {
+                fee_not_sent += &fee_slice;
+            }
         }
 
-        let rounding_error = fee_amount - &(fee_slice * slices);
+        let rounding_error = fee_amount - &fee_sent - &fee_not_sent;
         if rounding_error > 0 {
             self.reinject(fee_token, &rounding_error);
         }
+        fee_not_sent
+    }
+
+    fn get_total_fee_share(&self) -> u64 {
+        self.destination_map()
+            .keys()
+            .map(|fee_address| self.fee_destination_share(&fee_address).get())
+            .sum()
     }
 
+    // Returns false when the slice could not be sent, in which case it is left to the caller.
     fn send_fee_slice(
         &self,
         fee_token: &TokenIdentifier,
@@ -160,9 +265,10 @@ pub trait FeeModule:
         requested_fee_token: &TokenIdentifier,
         first_token_id: &TokenIdentifier,
         second_token_id: &TokenIdentifier,
-    ) {
+    ) -> bool {
         if self.can_send_fee_directly(fee_token, requested_fee_token) {
             self.send_fee_or_burn_on_zero_address(fee_token, fee_slice, fee_address);
+            true
         } else if self.can_resolve_swap_locally(
             fee_token,
             requested_fee_token,
@@ -173,19 +279,12 @@ pub trait FeeModule:
                 self.swap_safe_no_fee(first_token_id, second_token_id, fee_token, fee_slice);
             if to_send > 0 {
                 self.send_fee_or_burn_on_zero_address(requested_fee_token, &to_send, fee_address);
+                true
             } else {
-                self.reinject(fee_token, fee_slice);
+                false
             }
         } else if self.can_extern_swap_directly(fee_token, requested_fee_token) {
-            let resolved_externally = self.extern_swap_and_forward(
-                fee_token,
-                fee_slice,
-                requested_fee_token,
-                fee_address,
-            );
-            if !resolved_externally {
-                self.reinject(fee_token, fee_slice);
-            }
+            self.extern_swap_and_forward(fee_token, fee_slice, requested_fee_token, fee_address)
         } else if self.can_extern_swap_after_local_swap(
             first_token_id,
             second_token_id,
@@ -228,13 +327,13 @@ pub trait FeeModule:
                         &first_token_virtual_reserve,
                         &second_token_virtual_reserve,
                     );
-                    self.reinject(fee_token, fee_slice);
                 }
+                resolved_externally
             } else {
-                self.reinject(fee_token, fee_slice);
+                false
             }
         } else {
-            self.reinject(fee_token, fee_slice);
+            false
         }
     }
 
@@ -362,14 +461,136 @@ pub trait FeeModule:
         }
     }
 
+    #[endpoint(setFeeMode)]
+    fn set_fee_mode(&self, fee_mode: FeeMode) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(
+            fee_mode != FeeMode::LiquidityMint || (!self.is_stableswap() && !self.is_weighted()),
+            "Only available for constant product pairs"
+        );
+
+        self.mint_fee();
+        self.fee_mode().set(&fee_mode);
+        self.update_root_k_last();
+        Ok(())
+    }
+
+    // The sqrt(k) growth used by LiquidityMint also moves with the price of a weighted pool,
+    // so the two cannot be combined.
+    #[endpoint(setTokenWeights)]
+    fn set_token_weights(&self, first_token_weight: u64, second_token_weight: u64) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(
+            self.fee_mode().get() != FeeMode::LiquidityMint,
+            "Not available in LiquidityMint fee mode"
+        );
+
+        self.try_set_token_weights(first_token_weight, second_token_weight)
+    }
+
+    fn is_liquidity_mint_fee_active(&self) -> bool {
+        self.is_fee_enabled() && self.fee_mode().get() == FeeMode::LiquidityMint
+    }
+
+    // Mints the protocol share of the sqrt(k) growth since the last liquidity change as LP
+    // tokens kept by the pair. With t = total fee and s = special fee:
+    // liquidity = supply * s * (rk - rk_last) / ((t - s) * rk + s * rk_last)
+    fn mint_fee(&self) {
+        if !self.is_liquidity_mint_fee_active() {
+            return;
+        }
+
+        let root_k_last = self.root_k_last().get();
+        if root_k_last == 0 {
+            return;
+        }
+
+        let root_k = self.calculate_root_k_for_reserves();
+        if root_k <= root_k_last {
+            return;
+        }
+
+        let total_fee_percent = self.total_fee_percent().get();
+        let special_fee_percent = self.special_fee_percent().get();
+        if special_fee_percent == 0 {
+            return;
+        }
+
+        let total_supply = self.get_total_lp_token_supply();
+        let numerator = total_supply * (&root_k - &root_k_last) * special_fee_percent;
+        let denominator =
+            root_k * (total_fee_percent - special_fee_percent) + root_k_last * special_fee_percent;
+        let liquidity = numerator / denominator;
+        if liquidity > 0 {
+            self.mint_tokens(&self.lp_token_identifier().get(), &liquidity);
+            self.protocol_fee_liquidity()
+                .update(|amount| *amount += &liquidity);
+        }
+    }
+
+    fn update_root_k_last(&self) {
+        if self.is_liquidity_mint_fee_active() {
+            self.root_k_last()
+                .set(&self.calculate_root_k_for_reserves());
+        } else {
+            self.root_k_last().clear();
+        }
+    }
+
+    fn calculate_root_k_for_reserves(&self) -> BigUint {
+        let first_token_reserve = self.pair_reserve(&self.first_token_id().get()).get();
+        let second_token_reserve = self.pair_reserve(&self.second_token_id().get()).get();
+        let k = &first_token_reserve * &second_token_reserve;
+        // The arithmetic mean is never lower than the geometric one.
+        let initial_guess = first_token_reserve + second_token_reserve + 1u64;
+        self.nth_root(&k, 2, initial_guess)
+    }
+
+    // Redeems up to max_liquidity of the LP tokens minted as protocol fee and sends the
+    // tokens to the fee destinations the same way the per swap fees are sent.
+    #[endpoint(distributeProtocolFees)]
+    fn distribute_protocol_fees(&self, max_liquidity: BigUint) -> SCResult<()> {
+        self.require_permissions()?;
+        self.mint_fee();
+
+        let available_liquidity = self.protocol_fee_liquidity().get();
+        let liquidity = core::cmp::min(max_liquidity, available_liquidity.clone());
+        require!(liquidity > 0, "No protocol fees to distribute");
+
+        let (first_token_amount, second_token_amount) = self.pool_remove_liquidity(
+            liquidity.clone(),
+            BigUint::from(1u64),
+            BigUint::from(1u64),
+        )?;
+        self.burn_tokens(&self.lp_token_identifier().get(), &liquidity);
+        self.protocol_fee_liquidity()
+            .set(&(available_liquidity - liquidity));
+
+        let first_token_id = self.first_token_id().get();
+        let fee_not_sent = self.distribute_fee(&first_token_id, &first_token_amount);
+        self.reinject(&first_token_id, &fee_not_sent);
+
+        let second_token_id = self.second_token_id().get();
+        let fee_not_sent = self.distribute_fee(&second_token_id, &second_token_amount);
+        self.reinject(&second_token_id, &fee_not_sent);
+        self.update_root_k_last();
+        Ok(())
+    }
+
+    // While fee destination shares are set, a new destination needs its own share, which is
+    // taken from the other destinations in proportion to their shares. The share of a removed
+    // destination is given back to the others the same way.
     #[endpoint(setFeeOn)]
     fn set_fee_on(
         &self,
         enabled: bool,
         fee_to_address: ManagedAddress,
         fee_token: TokenIdentifier,
+        #[var_args] opt_fee_share: OptionalArg<u64>,
     ) -> SCResult<()> {
         self.require_permissions()?;
+        let is_split_by_shares = self.get_total_fee_share() != 0;
+        self.mint_fee();
         let is_dest = self
             .destination_map()
             .keys()
@@ -377,13 +598,107 @@ pub trait FeeModule:
 
         if enabled {
             require!(!is_dest, "Is already a fee destination");
+            let opt_fee_share = opt_fee_share.into_option();
+            if is_split_by_shares {
+                require!(opt_fee_share.is_some(), "Fee destination shares are set");
+                let fee_share = self.make_room_for_fee_share(opt_fee_share.unwrap())?;
+                self.fee_destination_share(&fee_to_address).set(&fee_share);
+            } else {
+                require!(opt_fee_share.is_none(), "Fee destination shares are not set");
+            }
             self.destination_map().insert(fee_to_address, fee_token);
         } else {
             require!(is_dest, "Is not a fee destination");
             let dest_fee_token = self.destination_map().get(&fee_to_address).unwrap();
             require!(fee_token == dest_fee_token, "Destination fee token differs");
             self.destination_map().remove(&fee_to_address);
+            if is_split_by_shares {
+                let fee_share = self.fee_destination_share(&fee_to_address).get();
+                self.fee_destination_share(&fee_to_address).clear();
+                self.spread_removed_fee_share(fee_share);
+            }
         }
+        self.update_root_k_last();
+        Ok(())
+    }
+
+    // Scales down the shares of the current destinations, such that they add up to 100%
+    // together with the returned share, which gets the rounding error.
+    fn make_room_for_fee_share(&self, fee_share: u64) -> SCResult<u64> {
+        require!(
+            fee_share != 0 && fee_share < MAX_FEE_SHARE,
+            "Invalid fee share"
+        );
+
+        let mut total_fee_share = 0u64;
+        for fee_address in self.destination_map().keys() {
+            let share_mapper = self.fee_destination_share(&fee_address);
+            let scaled_share = share_mapper.get() * (MAX_FEE_SHARE - fee_share) / MAX_FEE_SHARE;
+            require!(scaled_share != 0, "Fee share too high");
+            share_mapper.set(&scaled_share);
+            total_fee_share += scaled_share;
+        }
+        Ok(MAX_FEE_SHARE - total_fee_share)
+    }
+
+    // Scales up the shares of the remaining destinations back to 100%. The rounding error
+    // goes to the last of them.
+    fn spread_removed_fee_share(&self, removed_fee_share: u64) {
+        let remaining_fee_share = MAX_FEE_SHARE - removed_fee_share;
+        let mut total_fee_share = 0u64;
+        let mut last_fee_address = None;
+        for fee_address in self.destination_map().keys() {
+            let share_mapper = self.fee_destination_share(&fee_address);
+            let scaled_share = share_mapper.get() * MAX_FEE_SHARE / remaining_fee_share;
+            share_mapper.set(&scaled_share);
+            total_fee_share += scaled_share;
+            last_fee_address = Some(fee_address);
+        }
+
+        if let Some(fee_address) = last_fee_address {
+            self.fee_destination_share(&fee_address)
+                .update(|fee_share| *fee_share += MAX_FEE_SHARE - total_fee_share);
+        }
+    }
+
+    // Sets the shares of all the fee destinations at once, so they always add up to 100%.
+    // Without arguments, the shares are cleared and the fee is split in equal slices again.
+    #[endpoint(setFeeDestinationShares)]
+    fn set_fee_destination_shares(
+        &self,
+        #[var_args] fee_shares: MultiArgVec<MultiArg2<ManagedAddress, u64>>,
+    ) -> SCResult<()> {
+        self.require_permissions()?;
+        for fee_address in self.destination_map().keys() {
+            self.fee_destination_share(&fee_address).clear();
+        }
+        if fee_shares.is_empty() {
+            return Ok(());
+        }
+
+        require!(
+            fee_shares.len() == self.destination_map().len(),
+            "Every fee destination needs a share"
+        );
+        let mut total_fee_share = 0u64;
+        for fee_share_arg in fee_shares.into_vec() {
+            let (fee_address, fee_share) = fee_share_arg.into_tuple();
+            require!(
+                self.destination_map().contains_key(&fee_address),
+                "Is not a fee destination"
+            );
+            require!(fee_share != 0, "Fee share cannot be zero");
+            require!(
+                self.fee_destination_share(&fee_address).is_empty(),
+                "Duplicate fee destination"
+            );
+            self.fee_destination_share(&fee_address).set(&fee_share);
+            total_fee_share += fee_share;
+        }
+        require!(
+            total_fee_share == MAX_FEE_SHARE,
+            "Fee shares should sum up to 100%"
+        );
         Ok(())
     }
 
@@ -393,15 +708,49 @@ pub trait FeeModule:
     }
 
     #[view(getFeeDestinations)]
-    fn get_fee_destinations(&self) -> MultiResultVec<(ManagedAddress, TokenIdentifier)> {
+    fn get_fee_destinations(&self) -> MultiResultVec<(ManagedAddress, TokenIdentifier, u64)> {
         MultiResultVec::from_iter(
             self.destination_map()
                 .iter()
-                .map(|x| (x.0, x.1))
-                .collect::<Vec<(ManagedAddress, TokenIdentifier)>>(),
+                .map(|x| {
+                    let fee_share = self.fee_destination_share(&x.0).get();
+                    (x.0, x.1, fee_share)
+                })
+                .collect::<Vec<(ManagedAddress, TokenIdentifier, u64)>>(),
         )
     }
 
+    // Basis points of the special fee sent to a destination.
+    #[storage_mapper("fee_destination_share")]
+    fn fee_destination_share(&self, fee_address: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[view(getFeeMode)]
+    #[storage_mapper("fee_mode")]
+    fn fee_mode(&self) -> SingleValueMapper<FeeMode>;
+
+    #[view(getPendingFees)]
+    #[storage_mapper("pending_fees")]
+    fn pending_fees(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getSwapsSinceFeeDistribution)]
+    #[storage_mapper("swaps_since_fee_distribution")]
+    fn swaps_since_fee_distribution(&self) -> SingleValueMapper<u64>;
+
+    #[view(getLastFeeDistributionEpoch)]
+    #[storage_mapper("last_fee_distribution_epoch")]
+    fn last_fee_distribution_epoch(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("fee_distribution_swaps")]
+    fn fee_distribution_swaps(&self) -> SingleValueMapper<u64>;
+
+    #[view(getRootKLast)]
+    #[storage_mapper("root_k_last")]
+    fn root_k_last(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getProtocolFeeLiquidity)]
+    #[storage_mapper("protocol_fee_liquidity")]
+    fn protocol_fee_liquidity(&self) -> SingleValueMapper<BigUint>;
+
     #[view(getTrustedSwapPairs)]
     fn get_trusted_swap_pairs(&self) -> MultiResultVec<(TokenPair<Self::Api>, ManagedAddress)> {
         MultiResultVec::from_iter(
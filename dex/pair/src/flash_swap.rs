@@ -0,0 +1,103 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::amm;
+use super::config;
+use super::liquidity_pool;
+
+pub mod flash_swap_receiver_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait FlashSwapReceiver {
+        #[payable("*")]
+        #[endpoint(flashSwapCallback)]
+        fn flash_swap_callback(
+            &self,
+            #[payment_token] token_out: TokenIdentifier,
+            #[payment_amount] amount_out: BigUint,
+            caller: ManagedAddress,
+            user_data: BoxedBytes,
+        );
+    }
+}
+
+#[elrond_wasm::module]
+pub trait FlashSwapModule:
+    amm::AmmModule
+    + config::ConfigModule
+    + liquidity_pool::LiquidityPoolModule
+    + token_supply::TokenSupplyModule
+    + token_send::TokenSendModule
+{
+    #[proxy]
+    fn flash_swap_receiver_proxy(
+        &self,
+        to: ManagedAddress,
+    ) -> flash_swap_receiver_proxy::Proxy<Self::Api>;
+
+    // Called by the receiver from inside its callback to pay back the borrowed tokens.
+    #[payable("*")]
+    #[endpoint(repayFlashSwap)]
+    fn repay_flash_swap(
+        &self,
+        #[payment_token] token_id: TokenIdentifier,
+        #[payment_amount] amount: BigUint,
+    ) -> SCResult<()> {
+        require!(self.flash_swap_lock().get(), "No flash swap in progress");
+        require!(
+            token_id == self.first_token_id().get() || token_id == self.second_token_id().get(),
+            "Invalid token"
+        );
+        require!(amount > 0, "Zero amount");
+
+        self.flash_swap_repaid(&token_id)
+            .update(|repaid| *repaid += &amount);
+        Ok(())
+    }
+
+    fn require_not_locked(&self) -> SCResult<()> {
+        require!(!self.flash_swap_lock().get(), "Flash swap in progress");
+        Ok(())
+    }
+
+    fn take_flash_swap_repaid(&self, token_id: &TokenIdentifier) -> BigUint {
+        let repaid = self.flash_swap_repaid(token_id).get();
+        self.flash_swap_repaid(token_id).clear();
+        repaid
+    }
+
+    fn get_amount_after_total_fee(&self, amount: &BigUint) -> BigUint {
+        amount * (100000 - self.total_fee_percent().get()) / 100000u64
+    }
+
+    // Virtual reserves are only moved by the net result of the flash swap, without going
+    // below zero, so the anti front-running protection keeps working for this block.
+    fn update_virtual_reserves_after_flash_swap(
+        &self,
+        token_id: &TokenIdentifier,
+        amount_removed: &BigUint,
+        amount_added: &BigUint,
+    ) {
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        for token_side_id in [&first_token_id, &second_token_id].iter() {
+            self.pair_virtual_reserve(token_side_id, token_id)
+                .update(|reserve| {
+                    *reserve += amount_added;
+                    if &*reserve > amount_removed {
+                        *reserve -= amount_removed;
+                    } else {
+                        *reserve = BigUint::zero();
+                    }
+                });
+        }
+    }
+
+    #[view(isFlashSwapInProgress)]
+    #[storage_mapper("flash_swap_lock")]
+    fn flash_swap_lock(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("flash_swap_repaid")]
+    fn flash_swap_repaid(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+}
@@ -8,13 +8,18 @@ const DEFAULT_TRANSFER_EXEC_GAS_LIMIT: u64 = 35000000;
 const DEFAULT_EXTERN_SWAP_GAS_LIMIT: u64 = 50000000;
 
 mod amm;
+mod circuit_breaker;
 pub mod config;
+pub mod egld_wrapper;
 mod events;
 pub mod fee;
-mod liquidity_pool;
+pub mod flash_swap;
+pub mod liquidity_pool;
 mod oracle;
+mod quote;
 
 use config::State;
+use flash_swap::flash_swap_receiver_proxy::ProxyTrait as _;
 
 type AddLiquidityResultType<BigUint> =
     MultiResult3<EsdtTokenPayment<BigUint>, EsdtTokenPayment<BigUint>, EsdtTokenPayment<BigUint>>;
@@ -36,7 +41,12 @@ pub trait Pair:
     + oracle::OracleModule
     + token_supply::TokenSupplyModule
     + token_send::TokenSendModule
+    + deadline::DeadlineModule
     + events::EventsModule
+    + flash_swap::FlashSwapModule
+    + egld_wrapper::EgldWrapperModule
+    + quote::QuoteModule
+    + circuit_breaker::CircuitBreakerModule
 {
     #[init]
     fn init(
@@ -47,6 +57,7 @@ pub trait Pair:
         router_owner_address: ManagedAddress,
         total_fee_percent: u64,
         special_fee_percent: u64,
+        #[var_args] opt_amp_factor: OptionalArg<u64>,
     ) -> SCResult<()> {
         require!(
             first_token_id.is_valid_esdt_identifier(),
@@ -70,6 +81,9 @@ pub trait Pair:
             "Second token ID cannot be the same as LP token ID"
         );
         self.try_set_fee_percents(total_fee_percent, special_fee_percent)?;
+        if let OptionalArg::Some(amp_factor) = opt_amp_factor {
+            self.try_init_amp_factor(amp_factor)?;
+        }
 
         self.state().set_if_empty(&State::ActiveNoSwaps);
         self.transfer_exec_gas_limit()
@@ -90,16 +104,33 @@ pub trait Pair:
         &self,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
         #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
     ) -> SCResult<AddLiquidityResultType<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
         require!(self.is_active(), "Not active");
+        self.require_not_locked()?;
         self.broadcast_pair_reserves();
 
-        let payments = self.get_all_payments();
-        require!(payments.len() == 2, "bad payments len");
-
         let expected_first_token_id = self.first_token_id().get();
         let expected_second_token_id = self.second_token_id().get();
+
+        // EGLD can be paid instead of the wrapped EGLD side of the pair, the same as for swaps.
+        let mut payments = self.get_all_payments();
+        payments.retain(|payment| !payment.token_identifier.is_egld());
+        let egld_amount = self.call_value().egld_value();
+        let is_egld_payment = egld_amount > 0;
+        if is_egld_payment {
+            require!(payments.len() == 1, "bad payments len");
+            let wrapped_egld_token_id = self.resolve_egld_token_id(&TokenIdentifier::egld())?;
+            let wrapped_egld_payment = self.wrap_egld(&egld_amount);
+            if wrapped_egld_token_id == expected_first_token_id {
+                payments.insert(0, wrapped_egld_payment);
+            } else {
+                payments.push(wrapped_egld_payment);
+            }
+        }
+        require!(payments.len() == 2, "bad payments len");
         require!(
             payments[0].token_identifier == expected_first_token_id,
             "bad first payment"
@@ -131,6 +162,7 @@ pub trait Pair:
         );
         self.broadcast_pair_reserves();
 
+        self.mint_fee();
         let old_k = self.calculate_k_for_reserves();
         let (first_token_amount, second_token_amount) = self.calculate_optimal_amounts(
             first_token_amount_desired.clone(),
@@ -147,6 +179,7 @@ pub trait Pair:
         // Once liquidity has been added, the new K should always be greater than the old K.
         let new_k = self.calculate_k_for_reserves();
         self.validate_k_invariant_strict(&old_k, &new_k)?;
+        self.update_root_k_last();
 
         let lp_token_id = self.lp_token_identifier().get();
         self.mint_tokens(&lp_token_id, &liquidity);
@@ -157,6 +190,16 @@ pub trait Pair:
         payments.push(self.create_payment(&expected_second_token_id, 0, &second_token_unused));
 
         let caller = self.blockchain().get_caller();
+        if is_egld_payment {
+            let wrapped_egld_token_id = self.wrapped_egld_token_id().get();
+            let egld_unused = if wrapped_egld_token_id == expected_first_token_id {
+                &first_token_unused
+            } else {
+                &second_token_unused
+            };
+            self.transfer_egld_from_wrapped(&caller, egld_unused, &opt_accept_funds_func)?;
+            payments.retain(|payment| payment.token_identifier != wrapped_egld_token_id);
+        }
         self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
 
         self.broadcast_pair_reserves();
@@ -187,8 +230,11 @@ pub trait Pair:
         #[payment_amount] liquidity: BigUint,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
         #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
     ) -> SCResult<RemoveLiquidityResultType<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
+        self.require_not_locked()?;
         require!(
             !self.lp_token_identifier().is_empty(),
             "LP token not issued"
@@ -199,6 +245,7 @@ pub trait Pair:
         require!(token_id == lp_token_id, "Wrong liquidity token");
         self.broadcast_pair_reserves();
 
+        self.mint_fee();
         let old_k = self.calculate_k_for_reserves();
         let (first_token_amount, second_token_amount) = self.pool_remove_liquidity(
             liquidity.clone(),
@@ -212,6 +259,7 @@ pub trait Pair:
         // Once liquidity has been removed, the new K should always be lesser than the old K.
         let new_k = self.calculate_k_for_reserves();
         self.validate_k_invariant_strict(&new_k, &old_k)?;
+        self.update_root_k_last();
 
         let mut payments = Vec::new();
         payments.push(self.create_payment(&first_token_id, 0, &first_token_amount));
@@ -238,6 +286,193 @@ pub trait Pair:
         )))
     }
 
+    #[payable("*")]
+    #[endpoint(addLiquiditySingleToken)]
+    fn add_liquidity_single_token(
+        &self,
+        #[payment_token] token_in: TokenIdentifier,
+        #[payment_amount] amount_in: BigUint,
+        liquidity_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
+        #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
+    ) -> SCResult<AddLiquidityResultType<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
+        require!(self.can_swap(), "Swap is not enabled");
+        self.require_not_locked()?;
+        require!(amount_in > 0, "Invalid amount_in");
+        require!(
+            !self.is_stableswap() && !self.is_weighted(),
+            "Only available for constant product pairs"
+        );
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let token_out = if token_in == first_token_id {
+            second_token_id.clone()
+        } else if token_in == second_token_id {
+            first_token_id.clone()
+        } else {
+            return sc_error!("Invalid token in");
+        };
+        let reserve_token_in = self.pair_reserve(&token_in).get();
+        require!(
+            reserve_token_in > 0 && self.pair_reserve(&token_out).get() > 0,
+            "Pair has no liquidity"
+        );
+
+        // Part of the input is swapped so that the rest of it and the swap output
+        // can be added in the ratio of the reserves.
+        let caller = self.blockchain().get_caller();
+        let swap_amount = self.get_optimal_swap_amount(&amount_in, &reserve_token_in);
+        require!(
+            swap_amount > 0 && swap_amount < amount_in,
+            "Amount in is too small"
+        );
+        let swapped_amount = self.perform_swap_fixed_input(
+            &caller,
+            &token_in,
+            &swap_amount,
+            &token_out,
+            &BigUint::zero(),
+        )?;
+        let remaining_amount = &amount_in - &swap_amount;
+
+        let (first_token_amount_desired, second_token_amount_desired) =
+            if token_in == first_token_id {
+                (remaining_amount, swapped_amount)
+            } else {
+                (swapped_amount, remaining_amount)
+            };
+        self.mint_fee();
+        let old_k = self.calculate_k_for_reserves();
+        let (first_token_amount, second_token_amount) = self.calculate_optimal_amounts(
+            first_token_amount_desired.clone(),
+            second_token_amount_desired.clone(),
+            BigUint::zero(),
+            BigUint::zero(),
+        )?;
+
+        let liquidity =
+            self.pool_add_liquidity(first_token_amount.clone(), second_token_amount.clone())?;
+        require!(
+            liquidity >= liquidity_min,
+            "Liquidity minted lesser than minimum"
+        );
+        let first_token_unused = &first_token_amount_desired - &first_token_amount;
+        let second_token_unused = &second_token_amount_desired - &second_token_amount;
+
+        // Once liquidity has been added, the new K should always be greater than the old K.
+        let new_k = self.calculate_k_for_reserves();
+        self.validate_k_invariant_strict(&old_k, &new_k)?;
+        self.update_root_k_last();
+
+        let lp_token_id = self.lp_token_identifier().get();
+        self.mint_tokens(&lp_token_id, &liquidity);
+
+        let mut payments = Vec::new();
+        payments.push(self.create_payment(&lp_token_id, 0, &liquidity));
+        payments.push(self.create_payment(&first_token_id, 0, &first_token_unused));
+        payments.push(self.create_payment(&second_token_id, 0, &second_token_unused));
+        self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
+
+        self.broadcast_pair_reserves();
+        self.emit_add_liquidity_event(
+            &caller,
+            &first_token_id,
+            &first_token_amount,
+            &second_token_id,
+            &second_token_amount,
+            &lp_token_id,
+            &liquidity,
+            &self.get_total_lp_token_supply(),
+            &self.pair_reserve(&first_token_id).get(),
+            &self.pair_reserve(&second_token_id).get(),
+        );
+        Ok(MultiResult3::from((
+            self.create_payment(&lp_token_id, 0, &liquidity),
+            self.create_payment(&first_token_id, 0, &first_token_amount),
+            self.create_payment(&second_token_id, 0, &second_token_amount),
+        )))
+    }
+
+    #[payable("*")]
+    #[endpoint(removeLiquiditySingleToken)]
+    fn remove_liquidity_single_token(
+        &self,
+        #[payment_token] token_id: TokenIdentifier,
+        #[payment_amount] liquidity: BigUint,
+        token_out: TokenIdentifier,
+        amount_out_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
+        #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
+    ) -> SCResult<EsdtTokenPayment<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
+        require!(self.can_swap(), "Swap is not enabled");
+        self.require_not_locked()?;
+        require!(
+            !self.lp_token_identifier().is_empty(),
+            "LP token not issued"
+        );
+        let lp_token_id = self.lp_token_identifier().get();
+        require!(token_id == lp_token_id, "Wrong liquidity token");
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let token_in = if token_out == first_token_id {
+            second_token_id.clone()
+        } else if token_out == second_token_id {
+            first_token_id.clone()
+        } else {
+            return sc_error!("Invalid token out");
+        };
+        self.broadcast_pair_reserves();
+
+        let caller = self.blockchain().get_caller();
+        self.mint_fee();
+        let old_k = self.calculate_k_for_reserves();
+        let (first_token_amount, second_token_amount) =
+            self.pool_remove_liquidity(liquidity.clone(), BigUint::zero(), BigUint::zero())?;
+
+        // Once liquidity has been removed, the new K should always be lesser than the old K.
+        let new_k = self.calculate_k_for_reserves();
+        self.validate_k_invariant_strict(&new_k, &old_k)?;
+        self.update_root_k_last();
+        self.burn_tokens(&token_id, &liquidity);
+
+        self.emit_remove_liquidity_event(
+            &caller,
+            &first_token_id,
+            &first_token_amount,
+            &second_token_id,
+            &second_token_amount,
+            &lp_token_id,
+            &liquidity,
+            &self.get_total_lp_token_supply(),
+            &self.pair_reserve(&first_token_id).get(),
+            &self.pair_reserve(&second_token_id).get(),
+        );
+
+        // The other token is swapped back into the pair for the requested one.
+        let (amount_removed, amount_to_swap) = if token_out == first_token_id {
+            (first_token_amount, second_token_amount)
+        } else {
+            (second_token_amount, first_token_amount)
+        };
+        let swapped_amount = self.perform_swap_fixed_input(
+            &caller,
+            &token_in,
+            &amount_to_swap,
+            &token_out,
+            &BigUint::zero(),
+        )?;
+        let amount_out = amount_removed + swapped_amount;
+        require!(
+            amount_out >= amount_out_min,
+            "Computed amount out lesser than minimum amount out"
+        );
+
+        self.transfer_execute_custom(&caller, &token_out, 0, &amount_out, &opt_accept_funds_func)?;
+        Ok(self.create_payment(&token_out, 0, &amount_out))
+    }
+
     #[payable("*")]
     #[endpoint(removeLiquidityAndBuyBackAndBurnToken)]
     fn remove_liquidity_and_burn_token(
@@ -248,6 +483,7 @@ pub trait Pair:
     ) -> SCResult<()> {
         let caller = self.blockchain().get_caller();
         self.require_whitelisted(&caller)?;
+        self.require_not_locked()?;
 
         require!(
             !self.lp_token_identifier().is_empty(),
@@ -261,6 +497,7 @@ pub trait Pair:
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
 
+        self.mint_fee();
         let first_token_min_amount = self.types().big_uint_from(1u64);
         let second_token_min_amount = self.types().big_uint_from(1u64);
         let (first_token_amount, second_token_amount) = self.pool_remove_liquidity(
@@ -270,7 +507,7 @@ pub trait Pair:
         )?;
 
         let dest_address = self.types().managed_address_zero();
-        self.send_fee_slice(
+        let is_first_token_sent = self.send_fee_slice(
             &first_token_id,
             &first_token_amount,
             &dest_address,
@@ -278,7 +515,10 @@ pub trait Pair:
             &first_token_id,
             &second_token_id,
         );
-        self.send_fee_slice(
+        if !is_first_token_sent {
+            self.reinject(&first_token_id, &first_token_amount);
+        }
+        let is_second_token_sent = self.send_fee_slice(
             &second_token_id,
             &second_token_amount,
             &dest_address,
@@ -286,7 +526,11 @@ pub trait Pair:
             &first_token_id,
             &second_token_id,
         );
+        if !is_second_token_sent {
+            self.reinject(&second_token_id, &second_token_amount);
+        }
         self.burn_tokens(&token_in, &amount_in);
+        self.update_root_k_last();
 
         Ok(())
     }
@@ -302,6 +546,7 @@ pub trait Pair:
     ) -> SCResult<()> {
         let caller = self.blockchain().get_caller();
         self.require_whitelisted(&caller)?;
+        self.require_not_locked()?;
 
         require!(self.can_swap(), "Swap is not enabled");
         require!(amount_in > 0, "Zero input");
@@ -350,10 +595,17 @@ pub trait Pair:
         #[payment_amount] amount_in: BigUint,
         token_out: TokenIdentifier,
         amount_out_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
         #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
     ) -> SCResult<SwapTokensFixedInputResultType<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
         require!(self.can_swap(), "Swap is not enabled");
+        self.require_not_locked()?;
         require!(amount_in > 0, "Invalid amount_in");
+        let is_egld_payment = token_in.is_egld();
+        let token_out_requested = token_out.clone();
+        let token_in = self.resolve_egld_token_id(&token_in)?;
+        let token_out = self.resolve_egld_token_id(&token_out)?;
         require!(token_in != token_out, "Swap with same token");
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
@@ -365,21 +617,52 @@ pub trait Pair:
             token_out == first_token_id || token_out == second_token_id,
             "Invalid token out"
         );
+        if is_egld_payment {
+            self.wrap_egld(&amount_in);
+        }
+
+        let caller = self.blockchain().get_caller();
+        let amount_out = self.perform_swap_fixed_input(
+            &caller,
+            &token_in,
+            &amount_in,
+            &token_out,
+            &amount_out_min,
+        )?;
+        self.transfer_token_or_egld(
+            &caller,
+            &token_out_requested,
+            &amount_out,
+            &opt_accept_funds_func,
+        )?;
+
+        Ok(self.create_payment(&token_out_requested, 0, &amount_out))
+    }
+
+    fn perform_swap_fixed_input(
+        &self,
+        caller: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        token_out: &TokenIdentifier,
+        amount_out_min: &BigUint,
+    ) -> SCResult<BigUint> {
         self.broadcast_pair_reserves();
         self.update_virtual_reserves_on_block_change();
-        let old_k = self.calculate_k_for_virtual_reserves(&token_in);
+        self.record_block_reference_price();
+        let old_k = self.calculate_k_for_virtual_reserves(token_in);
 
-        let mut reserve_token_out = self.pair_virtual_reserve(&token_in, &token_out).get();
+        let mut reserve_token_out = self.pair_virtual_reserve(token_in, token_out).get();
         require!(
-            reserve_token_out > amount_out_min,
+            &reserve_token_out > amount_out_min,
             "Insufficient reserve for token out"
         );
 
-        let mut reserve_token_in = self.pair_virtual_reserve(&token_in, &token_in).get();
+        let mut reserve_token_in = self.pair_virtual_reserve(token_in, token_in).get();
         let amount_out_optimal =
-            self.get_amount_out(&amount_in, &reserve_token_in, &reserve_token_out);
+            self.swap_amount_out(token_in, amount_in, &reserve_token_in, &reserve_token_out);
         require!(
-            amount_out_optimal >= amount_out_min,
+            &amount_out_optimal >= amount_out_min,
             "Computed amount out lesser than minimum amount out"
         );
         require!(
@@ -388,54 +671,46 @@ pub trait Pair:
         );
         require!(amount_out_optimal != 0, "Optimal value is zero");
 
-        let caller = self.blockchain().get_caller();
-
         let mut fee_amount = BigUint::zero();
         let mut amount_in_after_fee = amount_in.clone();
         if self.is_fee_enabled() {
-            fee_amount = self.get_special_fee_from_input(&amount_in);
+            fee_amount = self.get_special_fee_from_input(amount_in);
             amount_in_after_fee -= &fee_amount;
         }
 
         reserve_token_in += &amount_in_after_fee;
         reserve_token_out -= &amount_out_optimal;
         self.set_virtual_reserves(
-            &token_in,
-            &token_in,
-            &token_out,
+            token_in,
+            token_in,
+            token_out,
             &reserve_token_in,
             &reserve_token_out,
         );
-        self.try_decrease_token_reserve(&token_out, &amount_out_optimal)?;
-        self.increase_token_reserve(&token_in, &amount_in_after_fee);
+        self.try_decrease_token_reserve(token_out, &amount_out_optimal)?;
+        self.increase_token_reserve(token_in, &amount_in_after_fee);
 
         // A swap should not decrease the value of K. Should either be greater or equal.
-        let new_k = self.calculate_k_for_virtual_reserves(&token_in);
+        let new_k = self.calculate_k_for_virtual_reserves(token_in);
         self.validate_k_invariant(&old_k, &new_k)?;
+        self.check_circuit_breaker(caller)?;
 
         //The transaction was made. We are left with $(fee) of $(token_in) as fee.
         if self.is_fee_enabled() {
-            self.send_fee(&token_in, &fee_amount);
+            self.send_fee(token_in, &fee_amount);
         }
-        self.transfer_execute_custom(
-            &caller,
-            &token_out,
-            0,
-            &amount_out_optimal,
-            &opt_accept_funds_func,
-        )?;
 
         self.emit_swap_event(
-            &caller,
-            &token_in,
-            &amount_in,
-            &token_out,
+            caller,
+            token_in,
+            amount_in,
+            token_out,
             &amount_out_optimal,
             &fee_amount,
             &reserve_token_in,
             &reserve_token_out,
         );
-        Ok(self.create_payment(&token_out, 0, &amount_out_optimal))
+        Ok(amount_out_optimal)
     }
 
     #[payable("*")]
@@ -446,10 +721,17 @@ pub trait Pair:
         #[payment_amount] amount_in_max: BigUint,
         token_out: TokenIdentifier,
         amount_out: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
         #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
     ) -> SCResult<SwapTokensFixedOutputResultType<Self::Api>> {
+        self.require_deadline_not_passed(&opt_deadline)?;
         require!(self.can_swap(), "Swap is not enabled");
+        self.require_not_locked()?;
         require!(amount_in_max > 0, "Invalid amount_in");
+        let token_in_paid = token_in.clone();
+        let token_out_requested = token_out.clone();
+        let token_in = self.resolve_egld_token_id(&token_in)?;
+        let token_out = self.resolve_egld_token_id(&token_out)?;
         require!(token_in != token_out, "Invalid swap with same token");
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
@@ -462,8 +744,12 @@ pub trait Pair:
             "Invalid token out"
         );
         require!(amount_out != 0, "Desired amount out cannot be zero");
+        if token_in_paid.is_egld() {
+            self.wrap_egld(&amount_in_max);
+        }
         self.broadcast_pair_reserves();
         self.update_virtual_reserves_on_block_change();
+        self.record_block_reference_price();
         let old_k = self.calculate_k_for_virtual_reserves(&token_in);
 
         let mut reserve_token_out = self.pair_virtual_reserve(&token_in, &token_out).get();
@@ -473,8 +759,12 @@ pub trait Pair:
         );
 
         let mut reserve_token_in = self.pair_virtual_reserve(&token_in, &token_in).get();
-        let amount_in_optimal =
-            self.get_amount_in(&amount_out, &reserve_token_in, &reserve_token_out);
+        let amount_in_optimal = self.swap_amount_in(
+            &token_in,
+            &amount_out,
+            &reserve_token_in,
+            &reserve_token_out,
+        );
         require!(
             amount_in_optimal <= amount_in_max,
             "Computed amount in greater than maximum amount in"
@@ -505,16 +795,33 @@ pub trait Pair:
         // A swap should not decrease the value of K. Should either be greater or equal.
         let new_k = self.calculate_k_for_virtual_reserves(&token_in);
         self.validate_k_invariant(&old_k, &new_k)?;
+        self.check_circuit_breaker(&caller)?;
 
         //The transaction was made. We are left with $(fee) of $(token_in) as fee.
         if self.is_fee_enabled() {
             self.send_fee(&token_in, &fee_amount);
         }
 
-        let mut payments = Vec::new();
-        payments.push(self.create_payment(&token_out, 0, &amount_out));
-        payments.push(self.create_payment(&token_in, 0, &residuum));
-        self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
+        // EGLD cannot be sent in the same transfer with ESDTs.
+        if token_in_paid.is_egld() || token_out_requested.is_egld() {
+            self.transfer_token_or_egld(
+                &caller,
+                &token_out_requested,
+                &amount_out,
+                &opt_accept_funds_func,
+            )?;
+            self.transfer_token_or_egld(
+                &caller,
+                &token_in_paid,
+                &residuum,
+                &opt_accept_funds_func,
+            )?;
+        } else {
+            let mut payments = Vec::new();
+            payments.push(self.create_payment(&token_out, 0, &amount_out));
+            payments.push(self.create_payment(&token_in, 0, &residuum));
+            self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
+        }
 
         self.emit_swap_event(
             &caller,
@@ -527,16 +834,248 @@ pub trait Pair:
             &reserve_token_out,
         );
         Ok(MultiResult2::from((
-            self.create_payment(&token_out, 0, &amount_out),
-            self.create_payment(&token_in, 0, &residuum),
+            self.create_payment(&token_out_requested, 0, &amount_out),
+            self.create_payment(&token_in_paid, 0, &residuum),
         )))
     }
 
-    fn broadcast_pair_reserves(&self) {
-        self.update_price_record(
-            &self.pair_reserve(&self.first_token_id().get()).get(),
-            &self.pair_reserve(&self.second_token_id().get()).get(),
+    #[endpoint(flashSwap)]
+    fn flash_swap(
+        &self,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+        receiver: ManagedAddress,
+        user_data: BoxedBytes,
+    ) -> SCResult<()> {
+        require!(self.can_swap(), "Swap is not enabled");
+        self.require_not_locked()?;
+        require!(amount_out > 0, "Zero output");
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let token_in = if token_out == first_token_id {
+            second_token_id.clone()
+        } else if token_out == second_token_id {
+            first_token_id.clone()
+        } else {
+            return sc_error!("Invalid token out");
+        };
+        require!(
+            self.blockchain().is_smart_contract(&receiver),
+            "Receiver is not a smart contract"
+        );
+        self.broadcast_pair_reserves();
+        self.update_virtual_reserves_on_block_change();
+        self.record_block_reference_price();
+
+        let reserve_token_in = self.pair_reserve(&token_in).get();
+        let reserve_token_out = self.pair_reserve(&token_out).get();
+        let old_k = self.calculate_k_for_reserves();
+        self.try_decrease_token_reserve(&token_out, &amount_out)?;
+
+        let caller = self.blockchain().get_caller();
+        self.flash_swap_lock().set(&true);
+        self.flash_swap_receiver_proxy(receiver.clone())
+            .flash_swap_callback(
+                token_out.clone(),
+                amount_out.clone(),
+                caller.clone(),
+                user_data,
+            )
+            .execute_on_dest_context();
+        self.flash_swap_lock().clear();
+
+        let amount_in_repaid = self.take_flash_swap_repaid(&token_in);
+        let amount_out_repaid = self.take_flash_swap_repaid(&token_out);
+
+        // The repaid amounts are charged the total fee, as a regular swap would be,
+        // before checking that K did not decrease.
+        let reserve_token_out_after_fee =
+            &reserve_token_out + &self.get_amount_after_total_fee(&amount_out_repaid);
+        require!(
+            reserve_token_out_after_fee > amount_out,
+            "Flash swap was not repaid"
+        );
+        let reserve_token_out_after_fee = reserve_token_out_after_fee - &amount_out;
+        let reserve_token_in_after_fee =
+            &reserve_token_in + &self.get_amount_after_total_fee(&amount_in_repaid);
+
+        let new_k = if token_out == first_token_id {
+            self.calculate_k_for_amounts(&reserve_token_out_after_fee, &reserve_token_in_after_fee)
+        } else {
+            self.calculate_k_for_amounts(&reserve_token_in_after_fee, &reserve_token_out_after_fee)
+        };
+        self.validate_k_invariant(&old_k, &new_k)?;
+
+        let mut fee_amount_in = BigUint::zero();
+        let mut fee_amount_out = BigUint::zero();
+        if self.is_fee_enabled() {
+            fee_amount_in = self.get_special_fee_from_input(&amount_in_repaid);
+            fee_amount_out = self.get_special_fee_from_input(&amount_out_repaid);
+        }
+        let amount_in_added = &amount_in_repaid - &fee_amount_in;
+        let amount_out_added = &amount_out_repaid - &fee_amount_out;
+
+        self.increase_token_reserve(&token_in, &amount_in_added);
+        self.increase_token_reserve(&token_out, &amount_out_added);
+        self.update_virtual_reserves_after_flash_swap(
+            &token_in,
+            &BigUint::zero(),
+            &amount_in_added,
+        );
+        self.update_virtual_reserves_after_flash_swap(&token_out, &amount_out, &amount_out_added);
+        self.check_circuit_breaker(&caller)?;
+
+        if self.is_fee_enabled() {
+            self.send_fee(&token_in, &fee_amount_in);
+            self.send_fee(&token_out, &fee_amount_out);
+        }
+
+        self.emit_flash_swap_event(
+            &caller,
+            &receiver,
+            &token_out,
+            &amount_out,
+            &token_in,
+            &amount_in_repaid,
+            &amount_out_repaid,
+            &self.pair_reserve(&token_in).get(),
+            &self.pair_reserve(&token_out).get(),
+        );
+        Ok(())
+    }
+
+    // Sets the reserves to the actual balances of the pair, without the pending fees.
+    // Since donations followed by a sync move the price, the circuit breaker applies as well.
+    #[endpoint]
+    fn sync(&self) -> SCResult<()> {
+        self.require_not_locked()?;
+        require!(self.get_total_lp_token_supply() > 0, "No liquidity");
+        self.broadcast_pair_reserves();
+        self.update_virtual_reserves_on_block_change();
+        self.record_block_reference_price();
+        self.mint_fee();
+
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let first_token_reserve = self.get_available_balance(&first_token_id);
+        let second_token_reserve = self.get_available_balance(&second_token_id);
+        require!(
+            first_token_reserve > 0 && second_token_reserve > 0,
+            "Not enough balance"
+        );
+
+        self.set_reserve_and_adjust_virtual_reserves(&first_token_id, &first_token_reserve);
+        self.set_reserve_and_adjust_virtual_reserves(&second_token_id, &second_token_reserve);
+        self.update_root_k_last();
+
+        let caller = self.blockchain().get_caller();
+        self.check_circuit_breaker(&caller)?;
+        self.emit_sync_event(
+            &caller,
+            &first_token_id,
+            &first_token_reserve,
+            &second_token_id,
+            &second_token_reserve,
+        );
+        Ok(())
+    }
+
+    // Sends the balances exceeding the reserves and the pending fees to the given address.
+    #[endpoint]
+    fn skim(&self, to: ManagedAddress) -> SCResult<()> {
+        self.require_not_locked()?;
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let (first_token_excess, _) = self.get_reserve_discrepancy_for_token(&first_token_id);
+        let (second_token_excess, _) = self.get_reserve_discrepancy_for_token(&second_token_id);
+        require!(
+            first_token_excess > 0 || second_token_excess > 0,
+            "Nothing to skim"
+        );
+
+        let payments = [
+            self.create_payment(&first_token_id, 0, &first_token_excess),
+            self.create_payment(&second_token_id, 0, &second_token_excess),
+        ];
+        self.send_multiple_tokens_compact(&to, &payments, &OptionalArg::None)?;
+
+        self.emit_skim_event(
+            &self.blockchain().get_caller(),
+            &to,
+            &first_token_id,
+            &first_token_excess,
+            &second_token_id,
+            &second_token_excess,
+        );
+        Ok(())
+    }
+
+    // For each token, the amount held above the reserve and the amount missing from it.
+    #[view(getReserveDiscrepancy)]
+    fn get_reserve_discrepancy(&self) -> MultiResult4<BigUint, BigUint, BigUint, BigUint> {
+        let (first_token_excess, first_token_shortfall) =
+            self.get_reserve_discrepancy_for_token(&self.first_token_id().get());
+        let (second_token_excess, second_token_shortfall) =
+            self.get_reserve_discrepancy_for_token(&self.second_token_id().get());
+
+        (
+            first_token_excess,
+            first_token_shortfall,
+            second_token_excess,
+            second_token_shortfall,
         )
+            .into()
+    }
+
+    fn get_reserve_discrepancy_for_token(&self, token_id: &TokenIdentifier) -> (BigUint, BigUint) {
+        let balance = self.get_available_balance(token_id);
+        let reserve = self.pair_reserve(token_id).get();
+        if balance > reserve {
+            (balance - reserve, BigUint::zero())
+        } else {
+            (BigUint::zero(), reserve - balance)
+        }
+    }
+
+    // The pending fees of the Deferred fee mode are held by the pair, but are not part of the reserves.
+    fn get_available_balance(&self, token_id: &TokenIdentifier) -> BigUint {
+        let balance = self.blockchain().get_sc_balance(token_id, 0);
+        let pending_fees = self.pending_fees(token_id).get();
+        if balance > pending_fees {
+            balance - pending_fees
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    // The virtual reserves of both swap directions are moved by the same amount as the reserve.
+    fn set_reserve_and_adjust_virtual_reserves(
+        &self,
+        token_id: &TokenIdentifier,
+        reserve: &BigUint,
+    ) {
+        let old_reserve = self.pair_reserve(token_id).get();
+        self.pair_reserve(token_id).set(reserve);
+
+        for token_side_id in [self.first_token_id().get(), self.second_token_id().get()].iter() {
+            self.pair_virtual_reserve(token_side_id, token_id)
+                .update(|virtual_reserve| {
+                    *virtual_reserve += reserve;
+                    if *virtual_reserve > old_reserve {
+                        *virtual_reserve -= &old_reserve;
+                    } else {
+                        *virtual_reserve = BigUint::zero();
+                    }
+                });
+        }
+    }
+
+    // For weighted pairs, the recorded reserves are scaled by the weight of the other token,
+    // so that the cumulative prices and the price records hold the spot price.
+    fn broadcast_pair_reserves(&self) {
+        let (first_token_reserve, second_token_reserve) = self.get_weight_normalized_reserves();
+        self.update_price_cumulative(&first_token_reserve, &second_token_reserve);
+        self.update_price_record(&first_token_reserve, &second_token_reserve)
     }
 
     #[endpoint(setLpTokenIdentifier)]
@@ -603,8 +1142,12 @@ pub trait Pair:
 
         if token_in == first_token_id {
             require!(second_token_reserve > 0, "Zero reserves for second token");
-            let amount_out =
-                self.get_amount_out(&amount_in, &first_token_reserve, &second_token_reserve);
+            let amount_out = self.swap_amount_out(
+                &token_in,
+                &amount_in,
+                &first_token_reserve,
+                &second_token_reserve,
+            );
             require!(
                 second_token_reserve > amount_out,
                 "Not enough reserves for second token"
@@ -612,8 +1155,12 @@ pub trait Pair:
             Ok(amount_out)
         } else if token_in == second_token_id {
             require!(first_token_reserve > 0, "Zero reserves for first token");
-            let amount_out =
-                self.get_amount_out(&amount_in, &second_token_reserve, &first_token_reserve);
+            let amount_out = self.swap_amount_out(
+                &token_in,
+                &amount_in,
+                &second_token_reserve,
+                &first_token_reserve,
+            );
             require!(
                 first_token_reserve > amount_out,
                 "Not enough reserves first token"
@@ -645,8 +1192,12 @@ pub trait Pair:
                 first_token_reserve > amount_wanted,
                 "Not enough reserves for first token"
             );
-            let amount_in =
-                self.get_amount_in(&amount_wanted, &second_token_reserve, &first_token_reserve);
+            let amount_in = self.swap_amount_in(
+                &second_token_id,
+                &amount_wanted,
+                &second_token_reserve,
+                &first_token_reserve,
+            );
             Ok(amount_in)
         } else if token_wanted == second_token_id {
             let (first_token_reserve, second_token_reserve) = self.get_reserves_for_current_block(
@@ -658,14 +1209,39 @@ pub trait Pair:
                 second_token_reserve > amount_wanted,
                 "Not enough reserves for second token"
             );
-            let amount_in =
-                self.get_amount_in(&amount_wanted, &first_token_reserve, &second_token_reserve);
+            let amount_in = self.swap_amount_in(
+                &first_token_id,
+                &amount_wanted,
+                &first_token_reserve,
+                &second_token_reserve,
+            );
             Ok(amount_in)
         } else {
             sc_error!("Not a known token")
         }
     }
 
+    // The accumulators as they would be if updated in the current block.
+    #[view(getPriceCumulatives)]
+    fn get_price_cumulatives(&self) -> MultiResult3<BigUint, BigUint, u64> {
+        let (first_token_reserve, second_token_reserve) = self.get_weight_normalized_reserves();
+        let current_block = self.blockchain().get_block_nonce();
+        let last_block = self.price_cumulative_last_block().get();
+        let elapsed_blocks = if last_block == 0 {
+            0
+        } else {
+            current_block - last_block
+        };
+        let (first_token_price_cumulative, second_token_price_cumulative) = self
+            .compute_price_cumulatives(&first_token_reserve, &second_token_reserve, elapsed_blocks);
+        (
+            first_token_price_cumulative,
+            second_token_price_cumulative,
+            current_block,
+        )
+            .into()
+    }
+
     #[view(getEquivalent)]
     fn get_equivalent(&self, token_in: TokenIdentifier, amount_in: BigUint) -> SCResult<BigUint> {
         require!(amount_in > 0, "Zero input");
@@ -673,8 +1249,7 @@ pub trait Pair:
 
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
-        let first_token_reserve = self.pair_reserve(&first_token_id).get();
-        let second_token_reserve = self.pair_reserve(&second_token_id).get();
+        let (first_token_reserve, second_token_reserve) = self.get_weight_normalized_reserves();
 
         if first_token_reserve == 0 || second_token_reserve == 0 {
             return Ok(zero);
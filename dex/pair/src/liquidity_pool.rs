@@ -5,6 +5,11 @@ use super::amm;
 use super::config;
 
 const MINIMUM_LIQUIDITY: u64 = 1_000;
+const TOTAL_WEIGHT: u64 = 100;
+// Weights are kept between 20 and 80, in steps of 10, so that the reduced weight ratio
+// used as exponent by the weighted swap math is at most 7.
+const WEIGHT_STEP: u64 = 10;
+const MIN_TOKEN_WEIGHT: u64 = 20;
 
 #[elrond_wasm::module]
 pub trait LiquidityPoolModule:
@@ -26,7 +31,10 @@ pub trait LiquidityPoolModule:
         let mut liquidity: BigUint;
 
         if total_supply == 0 {
-            liquidity = core::cmp::min(first_token_amount.clone(), second_token_amount.clone());
+            liquidity = core::cmp::min(
+                self.weight_normalized_amount(&first_token_amount, &first_token),
+                self.weight_normalized_amount(&second_token_amount, &second_token),
+            );
             let minimum_liquidity = self.types().big_uint_from(MINIMUM_LIQUIDITY);
             require!(
                 liquidity > minimum_liquidity,
@@ -101,6 +109,8 @@ pub trait LiquidityPoolModule:
         Ok((first_token_amount, second_token_amount))
     }
 
+    // Liquidity is always added in the ratio of the reserves, which keeps the spot price
+    // unchanged for weighted pairs as well. Only the first deposit sets the price.
     fn calculate_optimal_amounts(
         &self,
         first_token_amount_desired: BigUint,
@@ -222,7 +232,7 @@ pub trait LiquidityPoolModule:
     fn calculate_k_for_reserves(&self) -> BigUint {
         let first_token_amount = self.pair_reserve(&self.first_token_id().get()).get();
         let second_token_amount = self.pair_reserve(&self.second_token_id().get()).get();
-        self.calculate_k_constant(&first_token_amount, &second_token_amount)
+        self.calculate_k_for_amounts(&first_token_amount, &second_token_amount)
     }
 
     fn calculate_k_for_virtual_reserves(&self, token_side_id: &TokenIdentifier) -> BigUint {
@@ -232,7 +242,141 @@ pub trait LiquidityPoolModule:
         let second_token_amount = self
             .pair_virtual_reserve(token_side_id, &self.second_token_id().get())
             .get();
-        self.calculate_k_constant(&first_token_amount, &second_token_amount)
+        self.calculate_k_for_amounts(&first_token_amount, &second_token_amount)
+    }
+
+    fn calculate_k_for_amounts(
+        &self,
+        first_token_amount: &BigUint,
+        second_token_amount: &BigUint,
+    ) -> BigUint {
+        if self.is_weighted() {
+            self.calculate_weighted_k_constant(
+                first_token_amount,
+                second_token_amount,
+                self.first_token_weight().get(),
+                self.second_token_weight().get(),
+            )
+        } else {
+            self.calculate_k_constant(first_token_amount, second_token_amount)
+        }
+    }
+
+    fn swap_amount_out(
+        &self,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        if self.is_weighted() {
+            let (weight_in, weight_out) = self.get_token_weights(token_in);
+            self.get_weighted_amount_out(amount_in, reserve_in, reserve_out, weight_in, weight_out)
+        } else {
+            self.get_amount_out(amount_in, reserve_in, reserve_out)
+        }
+    }
+
+    fn swap_amount_out_no_fee(
+        &self,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        if self.is_weighted() {
+            let (weight_in, weight_out) = self.get_token_weights(token_in);
+            self.get_weighted_amount_out_no_fee(
+                amount_in,
+                reserve_in,
+                reserve_out,
+                weight_in,
+                weight_out,
+            )
+        } else {
+            self.get_amount_out_no_fee(amount_in, reserve_in, reserve_out)
+        }
+    }
+
+    fn swap_amount_in(
+        &self,
+        token_in: &TokenIdentifier,
+        amount_out: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        if self.is_weighted() {
+            let (weight_in, weight_out) = self.get_token_weights(token_in);
+            self.get_weighted_amount_in(amount_out, reserve_in, reserve_out, weight_in, weight_out)
+        } else {
+            self.get_amount_in(amount_out, reserve_in, reserve_out)
+        }
+    }
+
+    #[inline]
+    fn is_weighted(&self) -> bool {
+        self.first_token_weight().get() != 0
+    }
+
+    fn get_token_weights(&self, token_in: &TokenIdentifier) -> (u64, u64) {
+        let first_token_weight = self.first_token_weight().get();
+        let second_token_weight = self.second_token_weight().get();
+        if token_in == &self.first_token_id().get() {
+            (first_token_weight, second_token_weight)
+        } else {
+            (second_token_weight, first_token_weight)
+        }
+    }
+
+    fn weight_normalized_amount(&self, amount: &BigUint, token_id: &TokenIdentifier) -> BigUint {
+        if !self.is_weighted() {
+            return amount.clone();
+        }
+
+        let (weight, _) = self.get_token_weights(token_id);
+        amount * (TOTAL_WEIGHT / 2) / weight
+    }
+
+    // Reserves scaled by the weight of the other token, such that their ratio is the spot price.
+    fn get_weight_normalized_reserves(&self) -> (BigUint, BigUint) {
+        let first_token_reserve = self.pair_reserve(&self.first_token_id().get()).get();
+        let second_token_reserve = self.pair_reserve(&self.second_token_id().get()).get();
+        if !self.is_weighted() {
+            return (first_token_reserve, second_token_reserve);
+        }
+
+        (
+            first_token_reserve * self.second_token_weight().get(),
+            second_token_reserve * self.first_token_weight().get(),
+        )
+    }
+
+    fn try_set_token_weights(
+        &self,
+        first_token_weight: u64,
+        second_token_weight: u64,
+    ) -> SCResult<()> {
+        require!(!self.is_stableswap(), "Stableswap pairs cannot be weighted");
+        require!(
+            self.pair_reserve(&self.first_token_id().get()).is_empty()
+                && self.pair_reserve(&self.second_token_id().get()).is_empty(),
+            "Pair already has liquidity"
+        );
+        require!(
+            first_token_weight + second_token_weight == TOTAL_WEIGHT,
+            "Weights should sum up to 100"
+        );
+        require!(
+            first_token_weight >= MIN_TOKEN_WEIGHT
+                && second_token_weight >= MIN_TOKEN_WEIGHT
+                && first_token_weight.is_multiple_of(WEIGHT_STEP)
+                && second_token_weight.is_multiple_of(WEIGHT_STEP),
+            "Bad weights"
+        );
+
+        self.first_token_weight().set(&first_token_weight);
+        self.second_token_weight().set(&second_token_weight);
+        Ok(())
     }
 
     fn swap_safe_no_fee(
@@ -266,7 +410,8 @@ pub trait LiquidityPoolModule:
             return big_zero;
         }
 
-        let amount_out = self.get_amount_out_no_fee(amount_in, &reserve_in, &reserve_out);
+        let amount_out =
+            self.swap_amount_out_no_fee(token_in, amount_in, &reserve_in, &reserve_out);
         if reserve_out <= amount_out || amount_out == 0 {
             return big_zero;
         }
@@ -344,6 +489,14 @@ pub trait LiquidityPoolModule:
     #[storage_mapper("second_token_id")]
     fn second_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
 
+    #[view(getFirstTokenWeight)]
+    #[storage_mapper("first_token_weight")]
+    fn first_token_weight(&self) -> SingleValueMapper<u64>;
+
+    #[view(getSecondTokenWeight)]
+    #[storage_mapper("second_token_weight")]
+    fn second_token_weight(&self) -> SingleValueMapper<u64>;
+
     #[view(getReserve)]
     #[storage_mapper("reserve")]
     fn pair_reserve(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
@@ -1,9 +1,12 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+use super::config;
+
 const PRICE_DIVISION_SAFETY_CONSTANT: u64 = 1_000_000_000_000;
-const RECORD_BLOCKS_FREQUENCY: u64 = 600;
-const RECORD_BUFFER_MAX_LEN: usize = 10_000;
+const DEFAULT_RECORD_BLOCKS_FREQUENCY: u64 = 600;
+const DEFAULT_RECORD_BUFFER_MAX_LEN: usize = 10_000;
+const MAX_REARRANGED_PRICE_RECORDS: usize = 1_000;
 
 type Nonce = u64;
 
@@ -15,8 +18,14 @@ pub struct PriceRecord<M: ManagedTypeApi> {
     end_block: Nonce,
 }
 
+pub struct TwapAccumulator<M: ManagedTypeApi> {
+    first_token_price_sum: BigUint<M>,
+    second_token_price_sum: BigUint<M>,
+    blocks: u64,
+}
+
 #[elrond_wasm_derive::module]
-pub trait OracleModule {
+pub trait OracleModule: config::ConfigModule + token_send::TokenSendModule {
     fn update_price_record(&self, first_token_reserve: &BigUint, second_token_reserve: &BigUint) {
         if first_token_reserve == &0 || second_token_reserve == &0 {
             return;
@@ -56,6 +65,55 @@ pub trait OracleModule {
         self.current_price_record().set(&current_record);
     }
 
+    // Uniswap V2 style accumulators: the price of the previous state is added once per block,
+    // multiplied by the number of blocks it was in effect for. A consumer can compute the average
+    // price between two snapshots as (cumulative_2 - cumulative_1) / (block_2 - block_1).
+    fn update_price_cumulative(
+        &self,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+    ) {
+        let current_block = self.blockchain().get_block_nonce();
+        let last_block = self.price_cumulative_last_block().get();
+        if current_block == last_block {
+            return;
+        }
+        if last_block == 0 {
+            self.price_cumulative_last_block().set(&current_block);
+            return;
+        }
+
+        let (first_token_price_cumulative, second_token_price_cumulative) = self
+            .compute_price_cumulatives(
+                first_token_reserve,
+                second_token_reserve,
+                current_block - last_block,
+            );
+        self.price_cumulative_first()
+            .set(&first_token_price_cumulative);
+        self.price_cumulative_second()
+            .set(&second_token_price_cumulative);
+        self.price_cumulative_last_block().set(&current_block);
+    }
+
+    fn compute_price_cumulatives(
+        &self,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+        elapsed_blocks: u64,
+    ) -> (BigUint, BigUint) {
+        let mut first_token_price_cumulative = self.price_cumulative_first().get();
+        let mut second_token_price_cumulative = self.price_cumulative_second().get();
+        if first_token_reserve != &0 && second_token_reserve != &0 {
+            first_token_price_cumulative +=
+                self.instant_price(second_token_reserve, first_token_reserve) * elapsed_blocks;
+            second_token_price_cumulative +=
+                self.instant_price(first_token_reserve, second_token_reserve) * elapsed_blocks;
+        }
+
+        (first_token_price_cumulative, second_token_price_cumulative)
+    }
+
     fn build_first_price_record(
         &self,
         current_info_block: Nonce,
@@ -71,7 +129,8 @@ pub trait OracleModule {
     }
 
     fn should_commit_current_record(&self, current_record: &PriceRecord<Self::Api>) -> bool {
-        current_record.end_block >= current_record.start_block + RECORD_BLOCKS_FREQUENCY
+        current_record.end_block
+            >= current_record.start_block + self.get_price_record_block_frequency()
     }
 
     fn update_current_record(
@@ -107,19 +166,26 @@ pub trait OracleModule {
         current_record.end_block = current_info_block;
     }
 
+    // The buffer only grows while the head is on its last record. A buffer that has wrapped
+    // around keeps overwriting its oldest records until the head gets back to the end, so
+    // growing it never has to move the records around.
     fn commit_current_record(&self, record: &PriceRecord<Self::Api>) {
         let len = self.price_records().len();
-        if len < RECORD_BUFFER_MAX_LEN {
+        let old_head = self.price_records_head().get();
+        if old_head == len && len < self.get_price_record_max_len() {
             self.price_records().push(record);
             self.price_records_head().set(&(len + 1));
         } else {
-            let old_head = self.price_records_head().get();
-            let new_head = (old_head + 1) % RECORD_BUFFER_MAX_LEN;
+            let new_head = self.next_price_record_index(old_head, len);
             self.price_records().set(new_head, record);
             self.price_records_head().set(&new_head);
         }
     }
 
+    fn next_price_record_index(&self, index: usize, records_len: usize) -> usize {
+        index % records_len + 1
+    }
+
     fn instant_price(&self, numerator: &BigUint, denominator: &BigUint) -> BigUint {
         &(numerator * &BigUint::from(PRICE_DIVISION_SAFETY_CONSTANT)) / denominator
     }
@@ -137,6 +203,11 @@ pub trait OracleModule {
     }
 
     fn circular_binary_search(&self, block: Nonce) -> Option<PriceRecord<Self::Api>> {
+        self.circular_binary_search_index(block)
+            .map(|index| self.price_records().get(index))
+    }
+
+    fn circular_binary_search_index(&self, block: Nonce) -> Option<usize> {
         let none = Option::None;
         let mut low = 1;
         let mut high = self.price_records().len();
@@ -150,7 +221,7 @@ pub trait OracleModule {
             let mid_elem = self.price_records().get(mid);
 
             if self.record_contains_block(&mid_elem, block) {
-                return Option::<PriceRecord<Self::Api>>::from(mid_elem);
+                return Option::Some(mid);
             }
 
             let low_elem = self.price_records().get(low);
@@ -179,6 +250,96 @@ pub trait OracleModule {
         record.start_block <= block && block <= record.end_block
     }
 
+    fn accumulate_record_for_range(
+        &self,
+        record: &PriceRecord<Self::Api>,
+        start_block: Nonce,
+        end_block: Nonce,
+        accumulator: &mut TwapAccumulator<Self::Api>,
+    ) {
+        let overlap_start = core::cmp::max(start_block, record.start_block);
+        let overlap_end = core::cmp::min(end_block, record.end_block);
+        if overlap_start > overlap_end {
+            return;
+        }
+
+        let overlap_blocks = overlap_end - overlap_start + 1;
+        accumulator.first_token_price_sum += &record.first_token_price * overlap_blocks;
+        accumulator.second_token_price_sum += &record.second_token_price * overlap_blocks;
+        accumulator.blocks += overlap_blocks;
+    }
+
+    // Every record is weighted by the number of blocks it has in common with the range.
+    // Blocks after the end of the current record are not yet recorded, so the range
+    // cannot go past it.
+    fn compute_twap(
+        &self,
+        start_block: Nonce,
+        end_block: Nonce,
+    ) -> SCResult<MultiResult2<BigUint, BigUint>> {
+        require!(start_block <= end_block, "Invalid block range");
+        require!(!self.current_price_record().is_empty(), "No price records");
+        let current_record = self.current_price_record().get();
+        require!(
+            end_block <= current_record.end_block,
+            "End block is not recorded yet"
+        );
+
+        let mut accumulator = TwapAccumulator {
+            first_token_price_sum: BigUint::zero(),
+            second_token_price_sum: BigUint::zero(),
+            blocks: 0,
+        };
+
+        if start_block < current_record.start_block {
+            let start_index = self.circular_binary_search_index(start_block);
+            require!(start_index.is_some(), "Start block is no longer recorded");
+
+            let records_len = self.price_records().len();
+            let newest_index = self.price_records_head().get();
+            let mut index = start_index.unwrap();
+            loop {
+                let record = self.price_records().get(index);
+                if record.start_block > end_block {
+                    break;
+                }
+
+                self.accumulate_record_for_range(&record, start_block, end_block, &mut accumulator);
+                if index == newest_index {
+                    break;
+                }
+                index = self.next_price_record_index(index, records_len);
+            }
+        }
+        self.accumulate_record_for_range(&current_record, start_block, end_block, &mut accumulator);
+        require!(accumulator.blocks != 0, "No price records for block range");
+
+        Ok((
+            accumulator.first_token_price_sum / accumulator.blocks,
+            accumulator.second_token_price_sum / accumulator.blocks,
+        )
+            .into())
+    }
+
+    #[view(getTwap)]
+    fn get_twap(
+        &self,
+        start_block: Nonce,
+        end_block: Nonce,
+    ) -> SCResult<MultiResult2<BigUint, BigUint>> {
+        self.compute_twap(start_block, end_block)
+    }
+
+    #[view(getTwapForLastBlocks)]
+    fn get_twap_for_last_blocks(&self, blocks: u64) -> SCResult<MultiResult2<BigUint, BigUint>> {
+        require!(blocks != 0, "Invalid number of blocks");
+        require!(!self.current_price_record().is_empty(), "No price records");
+        let end_block = self.current_price_record().get().end_block;
+        require!(end_block + 1 >= blocks, "Not enough blocks recorded");
+
+        self.compute_twap(end_block + 1 - blocks, end_block)
+    }
+
     #[view(getPriceRecordForBlock)]
     fn get_price_record_for_block(&self, block: Nonce) -> Option<PriceRecord<Self::Api>> {
         self.circular_binary_search(block)
@@ -191,6 +352,10 @@ pub trait OracleModule {
         end: usize,
     ) -> MultiResultVec<PriceRecord<Self::Api>> {
         let mut result = Vec::new();
+        let records_len = self.price_records().len();
+        if records_len == 0 {
+            return result.into();
+        }
 
         let mut current_index = start;
         loop {
@@ -203,7 +368,7 @@ pub trait OracleModule {
                         end_block: 0,
                     }),
             );
-            current_index = (current_index + 1) % RECORD_BUFFER_MAX_LEN;
+            current_index = self.next_price_record_index(current_index, records_len);
 
             if current_index == end {
                 break;
@@ -225,12 +390,72 @@ pub trait OracleModule {
 
     #[view(getPriceRecordsMaxLen)]
     fn get_price_record_max_len(&self) -> usize {
-        RECORD_BUFFER_MAX_LEN
+        if self.price_records_max_len().is_empty() {
+            DEFAULT_RECORD_BUFFER_MAX_LEN
+        } else {
+            self.price_records_max_len().get()
+        }
     }
 
     #[view(getPriceRecordBlockFrequency)]
     fn get_price_record_block_frequency(&self) -> u64 {
-        RECORD_BLOCKS_FREQUENCY
+        if self.price_record_block_frequency().is_empty() {
+            DEFAULT_RECORD_BLOCKS_FREQUENCY
+        } else {
+            self.price_record_block_frequency().get()
+        }
+    }
+
+    #[endpoint(setPriceRecordBlockFrequency)]
+    fn set_price_record_block_frequency(&self, block_frequency: u64) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(block_frequency != 0, "Invalid block frequency");
+
+        self.price_record_block_frequency().set(&block_frequency);
+        Ok(())
+    }
+
+    #[endpoint(setPriceRecordsMaxLen)]
+    fn set_price_records_max_len(&self, max_len: usize) -> SCResult<()> {
+        self.require_permissions()?;
+        require!(max_len != 0, "Invalid max len");
+
+        if max_len < self.price_records().len() {
+            self.rearrange_price_records(max_len)?;
+        }
+        self.price_records_max_len().set(&max_len);
+        Ok(())
+    }
+
+    // Rewrites the buffer from the oldest to the newest record, starting from index 1,
+    // keeping only the newest records that fit in the new size. The head ends up on the
+    // last record, which is the layout of a buffer that has not wrapped around yet.
+    // Clearing the buffer touches every record, so only small buffers can be shrunk.
+    fn rearrange_price_records(&self, new_max_len: usize) -> SCResult<()> {
+        let len = self.price_records().len();
+        require!(
+            len <= MAX_REARRANGED_PRICE_RECORDS,
+            "Too many price records to rearrange"
+        );
+
+        let mut index = self.next_price_record_index(self.price_records_head().get(), len);
+        for _ in new_max_len..len {
+            index = self.next_price_record_index(index, len);
+        }
+
+        let mut records = Vec::with_capacity(new_max_len);
+        for _ in 0..new_max_len {
+            records.push(self.price_records().get(index));
+            index = self.next_price_record_index(index, len);
+        }
+
+        let mut price_records = self.price_records();
+        price_records.clear();
+        for record in records.iter() {
+            price_records.push(record);
+        }
+        self.price_records_head().set(&new_max_len);
+        Ok(())
     }
 
     #[view(getCurrentPriceRecord)]
@@ -245,6 +470,24 @@ pub trait OracleModule {
     #[storage_mapper("price_records_head")]
     fn price_records_head(&self) -> SingleValueMapper<usize>;
 
+    #[view(getPriceCumulativeFirst)]
+    #[storage_mapper("price_cumulative_first")]
+    fn price_cumulative_first(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getPriceCumulativeSecond)]
+    #[storage_mapper("price_cumulative_second")]
+    fn price_cumulative_second(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getPriceCumulativeLastBlock)]
+    #[storage_mapper("price_cumulative_last_block")]
+    fn price_cumulative_last_block(&self) -> SingleValueMapper<Nonce>;
+
+    #[storage_mapper("price_record_block_frequency")]
+    fn price_record_block_frequency(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("price_records_max_len")]
+    fn price_records_max_len(&self) -> SingleValueMapper<usize>;
+
     #[storage_mapper("known_current_block")]
     fn known_current_block(&self) -> SingleValueMapper<Nonce>;
 }
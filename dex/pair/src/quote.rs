@@ -0,0 +1,184 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::amm;
+use super::config;
+use super::fee;
+use super::liquidity_pool;
+
+const PRICE_PRECISION: u64 = 1_000_000_000_000;
+const MAX_BASIS_POINTS: u64 = 10_000;
+
+// Spot prices are the amount of token out for one token in, multiplied by PRICE_PRECISION.
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct SwapQuote<M: ManagedTypeApi> {
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    amount_out: BigUint<M>,
+    total_fee: BigUint<M>,
+    special_fee: BigUint<M>,
+    lp_fee: BigUint<M>,
+    spot_price_before: BigUint<M>,
+    spot_price_after: BigUint<M>,
+    price_impact_basis_points: BigUint<M>,
+    reserve_in: BigUint<M>,
+    reserve_out: BigUint<M>,
+}
+
+#[elrond_wasm::module]
+pub trait QuoteModule:
+    amm::AmmModule
+    + config::ConfigModule
+    + fee::FeeModule
+    + liquidity_pool::LiquidityPoolModule
+    + token_supply::TokenSupplyModule
+    + token_send::TokenSendModule
+{
+    #[view(getSwapQuote)]
+    fn get_swap_quote(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+    ) -> SCResult<SwapQuote<Self::Api>> {
+        require!(amount_in > 0, "Zero input");
+        let token_out = self.get_other_token_id(&token_in)?;
+        let (reserve_in, reserve_out) = self.get_quote_reserves(&token_in, &token_out);
+        require!(reserve_out > 0, "Zero reserves for token out");
+
+        let amount_out = self.swap_amount_out(&token_in, &amount_in, &reserve_in, &reserve_out);
+        require!(
+            reserve_out > amount_out,
+            "Not enough reserves for token out"
+        );
+
+        Ok(self.build_swap_quote(
+            token_in,
+            amount_in,
+            token_out,
+            amount_out,
+            reserve_in,
+            reserve_out,
+        ))
+    }
+
+    #[view(getSwapQuoteFixedOutput)]
+    fn get_swap_quote_fixed_output(
+        &self,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+    ) -> SCResult<SwapQuote<Self::Api>> {
+        require!(amount_out > 0, "Zero input");
+        let token_in = self.get_other_token_id(&token_out)?;
+        let (reserve_in, reserve_out) = self.get_quote_reserves(&token_in, &token_out);
+        require!(
+            reserve_out > amount_out,
+            "Not enough reserves for token out"
+        );
+
+        let amount_in = self.swap_amount_in(&token_in, &amount_out, &reserve_in, &reserve_out);
+
+        Ok(self.build_swap_quote(
+            token_in,
+            amount_in,
+            token_out,
+            amount_out,
+            reserve_in,
+            reserve_out,
+        ))
+    }
+
+    fn get_other_token_id(&self, token_id: &TokenIdentifier) -> SCResult<TokenIdentifier> {
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        if token_id == &first_token_id {
+            Ok(second_token_id)
+        } else if token_id == &second_token_id {
+            Ok(first_token_id)
+        } else {
+            sc_error!("Not a known token")
+        }
+    }
+
+    // The virtual reserves a swap with token_in would use in the current block.
+    fn get_quote_reserves(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+    ) -> (BigUint, BigUint) {
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let (first_token_reserve, second_token_reserve) =
+            self.get_reserves_for_current_block(token_in, &first_token_id, &second_token_id);
+
+        if token_out == &second_token_id {
+            (first_token_reserve, second_token_reserve)
+        } else {
+            (second_token_reserve, first_token_reserve)
+        }
+    }
+
+    fn build_swap_quote(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+        reserve_in: BigUint,
+        reserve_out: BigUint,
+    ) -> SwapQuote<Self::Api> {
+        let total_fee = &amount_in * self.total_fee_percent().get() / 100000u64;
+        let special_fee = if self.is_fee_enabled() {
+            self.get_special_fee_from_input(&amount_in)
+        } else {
+            BigUint::zero()
+        };
+        let lp_fee = &total_fee - &special_fee;
+
+        let reserve_in_after = &reserve_in + &amount_in - &special_fee;
+        let reserve_out_after = &reserve_out - &amount_out;
+        let spot_price_before = self.get_spot_price(&token_in, &reserve_in, &reserve_out);
+        let spot_price_after =
+            self.get_spot_price(&token_in, &reserve_in_after, &reserve_out_after);
+        let price_impact_basis_points = if spot_price_before > spot_price_after {
+            (&spot_price_before - &spot_price_after) * MAX_BASIS_POINTS / &spot_price_before
+        } else {
+            BigUint::zero()
+        };
+
+        SwapQuote {
+            token_in,
+            amount_in,
+            token_out,
+            amount_out,
+            total_fee,
+            special_fee,
+            lp_fee,
+            spot_price_before,
+            spot_price_after,
+            price_impact_basis_points,
+            reserve_in,
+            reserve_out,
+        }
+    }
+
+    fn get_spot_price(
+        &self,
+        token_in: &TokenIdentifier,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        if reserve_in == &0 || reserve_out == &0 {
+            return BigUint::zero();
+        }
+
+        if self.is_stableswap() {
+            self.get_stableswap_spot_price(reserve_in, reserve_out, PRICE_PRECISION)
+        } else if self.is_weighted() {
+            let (weight_in, weight_out) = self.get_token_weights(token_in);
+            reserve_out * PRICE_PRECISION * weight_in / &(reserve_in * weight_out)
+        } else {
+            reserve_out * PRICE_PRECISION / reserve_in
+        }
+    }
+}
@@ -0,0 +1,65 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use pair::egld_wrapper::egld_wrapper_proxy;
+
+#[elrond_wasm::module]
+pub trait EgldWrapperModule: token_send::TokenSendModule {
+    #[proxy]
+    fn egld_wrapper_proxy(&self, to: ManagedAddress) -> egld_wrapper_proxy::Proxy<Self::Api>;
+
+    #[only_owner]
+    #[endpoint(setEgldWrapperAddress)]
+    fn set_egld_wrapper_address(&self, address: ManagedAddress) -> SCResult<()> {
+        let wrapped_egld_token_id: TokenIdentifier = self
+            .egld_wrapper_proxy(address.clone())
+            .get_wrapped_egld_token_id()
+            .execute_on_dest_context();
+        require!(
+            wrapped_egld_token_id.is_valid_esdt_identifier(),
+            "Invalid wrapped EGLD token ID"
+        );
+
+        self.egld_wrapper_address().set(&address);
+        self.wrapped_egld_token_id().set(&wrapped_egld_token_id);
+        Ok(())
+    }
+
+    fn require_egld_wrapper_set(&self) -> SCResult<()> {
+        require!(
+            !self.egld_wrapper_address().is_empty(),
+            "EGLD wrapper not set"
+        );
+        Ok(())
+    }
+
+    fn wrap_egld(&self, amount: &BigUint) -> EsdtTokenPayment<Self::Api> {
+        self.egld_wrapper_proxy(self.egld_wrapper_address().get())
+            .wrap_egld(amount.clone())
+            .execute_on_dest_context()
+    }
+
+    fn transfer_egld_from_wrapped(
+        &self,
+        to: &ManagedAddress,
+        amount: &BigUint,
+        opt_accept_funds_func: &OptionalArg<BoxedBytes>,
+    ) -> SCResult<()> {
+        if amount == &0 {
+            return Ok(());
+        }
+
+        self.egld_wrapper_proxy(self.egld_wrapper_address().get())
+            .unwrap_egld(self.wrapped_egld_token_id().get(), amount.clone())
+            .execute_on_dest_context();
+        self.transfer_egld_execute_custom(to, amount, opt_accept_funds_func)
+    }
+
+    #[view(getEgldWrapperAddress)]
+    #[storage_mapper("egld_wrapper_address")]
+    fn egld_wrapper_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getWrappedEgldTokenId)]
+    #[storage_mapper("wrapped_egld_token_id")]
+    fn wrapped_egld_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+}
@@ -14,6 +14,40 @@ pub struct CreatePairEvent<M: ManagedTypeApi> {
     timestamp: u64,
 }
 
+#[derive(TopEncode, NestedEncode)]
+pub struct SwapHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub amount_out: BigUint<M>,
+}
+
+#[derive(TopEncode)]
+pub struct MultiPairSwapEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    token_in: TokenIdentifier<M>,
+    amount_in: BigUint<M>,
+    token_out: TokenIdentifier<M>,
+    amount_out: BigUint<M>,
+    hops: Vec<SwapHop<M>>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TopEncode)]
+pub struct UpgradeAllPairsEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    start_index: usize,
+    next_index: usize,
+    total_pairs: usize,
+    completed: bool,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[elrond_wasm::module]
 pub trait EventsModule {
     fn emit_create_pair_event(
@@ -45,6 +79,60 @@ pub trait EventsModule {
         )
     }
 
+    fn emit_multi_pair_swap_event(
+        self,
+        caller: ManagedAddress,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+        hops: Vec<SwapHop<Self::Api>>,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.multi_pair_swap_event(
+            caller.clone(),
+            token_in.clone(),
+            token_out.clone(),
+            epoch,
+            MultiPairSwapEvent {
+                caller,
+                token_in,
+                amount_in,
+                token_out,
+                amount_out,
+                hops,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_upgrade_all_pairs_event(
+        self,
+        caller: ManagedAddress,
+        start_index: usize,
+        next_index: usize,
+        total_pairs: usize,
+        completed: bool,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.upgrade_all_pairs_event(
+            caller.clone(),
+            epoch,
+            UpgradeAllPairsEvent {
+                caller,
+                start_index,
+                next_index,
+                total_pairs,
+                completed,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     #[event("create_pair")]
     fn create_pair_event(
         self,
@@ -54,4 +142,22 @@ pub trait EventsModule {
         #[indexed] epoch: u64,
         swap_event: CreatePairEvent<Self::Api>,
     );
+
+    #[event("multi_pair_swap")]
+    fn multi_pair_swap_event(
+        self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] token_in: TokenIdentifier,
+        #[indexed] token_out: TokenIdentifier,
+        #[indexed] epoch: u64,
+        multi_pair_swap_event: MultiPairSwapEvent<Self::Api>,
+    );
+
+    #[event("upgrade_all_pairs")]
+    fn upgrade_all_pairs_event(
+        self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] epoch: u64,
+        upgrade_all_pairs_event: UpgradeAllPairsEvent<Self::Api>,
+    );
 }
@@ -10,13 +10,44 @@ pub struct PairTokens<M: ManagedTypeApi> {
     pub second_token_id: TokenIdentifier<M>,
 }
 
+// Several pairs can exist for the same tokens, one for each total fee percent.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, TypeAbi)]
+pub struct PairKey<M: ManagedTypeApi> {
+    pub first_token_id: TokenIdentifier<M>,
+    pub second_token_id: TokenIdentifier<M>,
+    pub total_fee_percent: u64,
+}
+
+// The stages a pair goes through before it can be used, in order. Each one is reached
+// once the action it names has succeeded, so a failed action can simply be retried.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, TypeAbi)]
+pub enum PairSetupStage {
+    Deployed,
+    LpTokenIssued,
+    LocalRolesSet,
+    Active,
+}
+
 #[derive(TopEncode, TopDecode, PartialEq, TypeAbi)]
 pub struct PairContractMetadata<M: ManagedTypeApi> {
     first_token_id: TokenIdentifier<M>,
     second_token_id: TokenIdentifier<M>,
+    total_fee_percent: u64,
     address: ManagedAddress<M>,
 }
 
+// A pair marked for delisting. It stays listed after its removal from pair_map, so that its
+// liquidity providers can still find it.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct DeprecatedPair<M: ManagedTypeApi> {
+    pub first_token_id: TokenIdentifier<M>,
+    pub second_token_id: TokenIdentifier<M>,
+    pub total_fee_percent: u64,
+    pub address: ManagedAddress<M>,
+    pub removal_block: u64,
+    pub removed: bool,
+}
+
 #[elrond_wasm::module]
 pub trait FactoryModule {
     fn init_factory(&self, pair_template_address_opt: Option<ManagedAddress>) {
@@ -35,6 +66,7 @@ pub trait FactoryModule {
         owner: &ManagedAddress,
         total_fee_percent: u64,
         special_fee_percent: u64,
+        amp_factor: u64,
     ) -> SCResult<ManagedAddress> {
         require!(
             !self.pair_template_address().is_empty(),
@@ -48,19 +80,23 @@ pub trait FactoryModule {
         arg_buffer.push_arg(owner);
         arg_buffer.push_arg(&total_fee_percent.to_be_bytes()[..]);
         arg_buffer.push_arg(&special_fee_percent.to_be_bytes()[..]);
+        if amp_factor != 0 {
+            arg_buffer.push_arg(&amp_factor.to_be_bytes()[..]);
+        }
 
         let (new_address, _) = self.raw_vm_api().deploy_from_source_contract(
             self.blockchain().get_gas_left(),
             &BigUint::zero(),
             &self.pair_template_address().get(),
-            CodeMetadata::UPGRADEABLE,
+            CodeMetadata::UPGRADEABLE | CodeMetadata::PAYABLE,
             &arg_buffer,
         );
 
         self.pair_map().insert(
-            PairTokens {
+            PairKey {
                 first_token_id: first_token_id.clone(),
                 second_token_id: second_token_id.clone(),
+                total_fee_percent,
             },
             new_address.clone(),
         );
@@ -71,21 +107,54 @@ pub trait FactoryModule {
                 self.blockchain().get_block_nonce(),
             ),
         );
+        self.pair_setup_stage(&new_address)
+            .set(&PairSetupStage::Deployed);
+        self.pair_creator(&new_address)
+            .set(&self.blockchain().get_caller());
         Ok(new_address)
     }
 
+    // The pair is moved to the fee tier of its new total fee percent.
     fn upgrade_pair(
         &self,
         pair_address: &ManagedAddress,
-        first_token_id: &TokenIdentifier,
-        second_token_id: &TokenIdentifier,
+        pair_key: PairKey<Self::Api>,
+        owner: &ManagedAddress,
+        total_fee_percent: u64,
+        special_fee_percent: u64,
+    ) {
+        self.upgrade_pair_contract(
+            pair_address,
+            &pair_key,
+            owner,
+            total_fee_percent,
+            special_fee_percent,
+            self.blockchain().get_gas_left(),
+        );
+
+        if pair_key.total_fee_percent != total_fee_percent {
+            let new_pair_key = PairKey {
+                first_token_id: pair_key.first_token_id.clone(),
+                second_token_id: pair_key.second_token_id.clone(),
+                total_fee_percent,
+            };
+            self.remove_pair_from_map(&pair_key);
+            self.pair_map().insert(new_pair_key, pair_address.clone());
+        }
+    }
+
+    fn upgrade_pair_contract(
+        &self,
+        pair_address: &ManagedAddress,
+        pair_key: &PairKey<Self::Api>,
         owner: &ManagedAddress,
         total_fee_percent: u64,
         special_fee_percent: u64,
+        gas: u64,
     ) {
         let mut arg_buffer = ManagedArgBuffer::new_empty(self.type_manager());
-        arg_buffer.push_arg(first_token_id);
-        arg_buffer.push_arg(second_token_id);
+        arg_buffer.push_arg(&pair_key.first_token_id);
+        arg_buffer.push_arg(&pair_key.second_token_id);
         arg_buffer.push_arg(self.blockchain().get_sc_address());
         arg_buffer.push_arg(owner);
         arg_buffer.push_arg(&total_fee_percent.to_be_bytes()[..]);
@@ -93,16 +162,96 @@ pub trait FactoryModule {
 
         self.raw_vm_api().upgrade_from_source_contract(
             pair_address,
-            self.blockchain().get_gas_left(),
+            gas,
             &BigUint::zero(),
             &self.pair_template_address().get(),
-            CodeMetadata::UPGRADEABLE,
+            CodeMetadata::UPGRADEABLE | CodeMetadata::PAYABLE,
             &arg_buffer,
         );
     }
 
+    // A batch upgrade in progress resumes after the last pair it upgraded, so when that pair
+    // leaves its place in pair_map, the pair before it takes over.
+    fn remove_pair_from_map(&self, pair_key: &PairKey<Self::Api>) -> Option<ManagedAddress> {
+        let pair_address = self.pair_map().get(pair_key)?;
+        let last_upgraded_pair = self.last_upgraded_pair();
+        if !last_upgraded_pair.is_empty() && last_upgraded_pair.get() == pair_address {
+            let mut previous_pair = None;
+            for address in self.pair_map().values() {
+                if address == pair_address {
+                    break;
+                }
+                previous_pair = Some(address);
+            }
+            match previous_pair {
+                Some(address) => last_upgraded_pair.set(&address),
+                None => last_upgraded_pair.clear(),
+            }
+        }
+        self.pair_map().remove(pair_key)
+    }
+
+    #[storage_mapper("pair_fee_tier_map")]
+    fn pair_map(&self) -> MapMapper<PairKey<Self::Api>, ManagedAddress>;
+
+    // Pairs recorded before fee tiers, left to be moved to pair_map by migratePairMap.
     #[storage_mapper("pair_map")]
-    fn pair_map(&self) -> MapMapper<PairTokens<Self::Api>, ManagedAddress>;
+    fn legacy_pair_map(&self) -> MapMapper<PairTokens<Self::Api>, ManagedAddress>;
+
+    // The tokens of a legacy pair can be given in either order.
+    fn get_legacy_pair_tokens(
+        &self,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+    ) -> Option<PairTokens<Self::Api>> {
+        let pair_tokens = PairTokens {
+            first_token_id: first_token_id.clone(),
+            second_token_id: second_token_id.clone(),
+        };
+        if self.legacy_pair_map().contains_key(&pair_tokens) {
+            return Some(pair_tokens);
+        }
+
+        let pair_tokens = PairTokens {
+            first_token_id: second_token_id.clone(),
+            second_token_id: first_token_id.clone(),
+        };
+        if self.legacy_pair_map().contains_key(&pair_tokens) {
+            return Some(pair_tokens);
+        }
+        None
+    }
+
+    // Legacy pairs are still found before migratePairMap moves them.
+    fn get_pair_tokens_by_address(
+        &self,
+        pair_address: &ManagedAddress,
+    ) -> Option<PairTokens<Self::Api>> {
+        let pair_tokens = self
+            .pair_map()
+            .iter()
+            .find(|(_, address)| address == pair_address)
+            .map(|(pair_key, _)| PairTokens {
+                first_token_id: pair_key.first_token_id,
+                second_token_id: pair_key.second_token_id,
+            });
+        if pair_tokens.is_some() {
+            return pair_tokens;
+        }
+
+        self.legacy_pair_map()
+            .iter()
+            .find(|(_, address)| address == pair_address)
+            .map(|(tokens, _)| tokens)
+    }
+
+    fn require_pair_map_migrated(&self) -> SCResult<()> {
+        require!(
+            self.legacy_pair_map().is_empty(),
+            "Pair map migration pending"
+        );
+        Ok(())
+    }
 
     #[view(getAllPairsManagedAddresses)]
     fn get_all_pairs_addresses(&self) -> MultiResultVec<ManagedAddress> {
@@ -111,7 +260,13 @@ pub trait FactoryModule {
 
     #[view(getAllPairTokens)]
     fn get_all_token_pairs(&self) -> MultiResultVec<PairTokens<Self::Api>> {
-        self.pair_map().keys().collect()
+        self.pair_map()
+            .keys()
+            .map(|pair_key| PairTokens {
+                first_token_id: pair_key.first_token_id,
+                second_token_id: pair_key.second_token_id,
+            })
+            .collect()
     }
 
     #[view(getAllPairContractMetadata)]
@@ -122,6 +277,7 @@ pub trait FactoryModule {
             .map(|x| PairContractMetadata {
                 first_token_id: x.0.first_token_id,
                 second_token_id: x.0.second_token_id,
+                total_fee_percent: x.0.total_fee_percent,
                 address: x.1,
             })
             .collect();
@@ -176,4 +332,37 @@ pub trait FactoryModule {
 
     #[storage_mapper("pair_temporary_owner")]
     fn pair_temporary_owner(&self) -> MapMapper<ManagedAddress, (ManagedAddress, u64)>;
+
+    // Pairs deployed before the setup was tracked have no stage and count as active.
+    fn get_pair_setup_stage(&self, pair_address: &ManagedAddress) -> PairSetupStage {
+        if self.pair_setup_stage(pair_address).is_empty() {
+            PairSetupStage::Active
+        } else {
+            self.pair_setup_stage(pair_address).get()
+        }
+    }
+
+    #[storage_mapper("pair_setup_stage")]
+    fn pair_setup_stage(&self, pair_address: &ManagedAddress) -> SingleValueMapper<PairSetupStage>;
+
+    #[storage_mapper("pair_setup_error")]
+    fn pair_setup_error(&self, pair_address: &ManagedAddress) -> SingleValueMapper<BoxedBytes>;
+
+    #[storage_mapper("pair_creator")]
+    fn pair_creator(&self, pair_address: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("lp_token_issue_in_progress")]
+    fn lp_token_issue_in_progress(&self, pair_address: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_lp_token_info")]
+    fn pair_lp_token_info(
+        &self,
+        pair_address: &ManagedAddress,
+    ) -> SingleValueMapper<(ManagedBuffer, ManagedBuffer)>;
+
+    #[storage_mapper("last_upgraded_pair")]
+    fn last_upgraded_pair(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("deprecated_pairs")]
+    fn deprecated_pairs(&self) -> MapMapper<ManagedAddress, DeprecatedPair<Self::Api>>;
 }
@@ -0,0 +1,174 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::egld_wrapper;
+use super::events;
+use super::factory;
+use super::pair_manager;
+use super::state;
+
+use pair::fee::ProxyTrait as _;
+
+// Left for registering the farm as a fee destination, after the deployment.
+const FEE_SETUP_GAS_RESERVE: u64 = 20_000_000;
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct FarmContractMetadata<M: ManagedTypeApi> {
+    pub farming_token_id: TokenIdentifier<M>,
+    pub reward_token_id: TokenIdentifier<M>,
+    pub pair_address: ManagedAddress<M>,
+    pub locked_asset_factory_address: ManagedAddress<M>,
+    pub division_safety_constant: BigUint<M>,
+    pub address: ManagedAddress<M>,
+}
+
+#[elrond_wasm::module]
+pub trait FarmFactoryModule:
+    state::StateModule
+    + factory::FactoryModule
+    + pair_manager::PairManagerModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + deadline::DeadlineModule
+{
+    // Deploys a farm for the LP token of the pair, owned by the Router's owner, and makes
+    // it a fee destination of the pair, receiving the fees in its reward token. The fee share
+    // is needed when the pair has fee destination shares set.
+    #[only_owner]
+    #[endpoint(createFarm)]
+    fn create_farm(
+        &self,
+        pair_address: ManagedAddress,
+        reward_token_id: TokenIdentifier,
+        division_safety_constant: BigUint,
+        #[var_args] opt_fee_share: OptionalArg<u64>,
+    ) -> SCResult<ManagedAddress> {
+        require!(self.is_active(), "Not active");
+        self.check_is_pair_sc(&pair_address)?;
+        require!(
+            !self.farm_template_address().is_empty(),
+            "farm contract template is empty"
+        );
+        require!(
+            !self.locked_asset_factory_address().is_empty(),
+            "Locked asset factory address not set"
+        );
+        require!(
+            reward_token_id.is_valid_esdt_identifier(),
+            "Reward token ID is not a valid esdt token ID"
+        );
+
+        let farming_token_id = self.get_lp_token_for_pair(&pair_address);
+        require!(farming_token_id.is_esdt(), "LP token not issued");
+        require!(
+            !self.farm_map().contains_key(&farming_token_id),
+            "Farm already exists"
+        );
+
+        let gas_left = self.blockchain().get_gas_left();
+        require!(gas_left > FEE_SETUP_GAS_RESERVE, "Not enough gas");
+
+        let mut farm_metadata = FarmContractMetadata {
+            farming_token_id: farming_token_id.clone(),
+            reward_token_id: reward_token_id.clone(),
+            pair_address: pair_address.clone(),
+            locked_asset_factory_address: self.locked_asset_factory_address().get(),
+            division_safety_constant,
+            address: self.types().managed_address_zero(),
+        };
+        let (farm_address, _) = self.raw_vm_api().deploy_from_source_contract(
+            gas_left - FEE_SETUP_GAS_RESERVE,
+            &BigUint::zero(),
+            &self.farm_template_address().get(),
+            CodeMetadata::UPGRADEABLE | CodeMetadata::PAYABLE,
+            &self.build_farm_init_args(&farm_metadata),
+        );
+        farm_metadata.address = farm_address.clone();
+        self.farm_map().insert(farming_token_id, farm_metadata);
+
+        self.pair_contract_proxy(pair_address)
+            .set_fee_on(true, farm_address.clone(), reward_token_id, opt_fee_share)
+            .execute_on_dest_context();
+
+        Ok(farm_address)
+    }
+
+    // Upgrades the farm to the current template, wiring it to the current locked asset factory.
+    #[only_owner]
+    #[endpoint(upgradeFarm)]
+    fn upgrade_farm(&self, farming_token_id: TokenIdentifier) -> SCResult<()> {
+        require!(self.is_active(), "Not active");
+        require!(
+            !self.farm_template_address().is_empty(),
+            "farm contract template is empty"
+        );
+
+        let farm_metadata = self.farm_map().get(&farming_token_id);
+        require!(farm_metadata.is_some(), "Farm does not exist");
+        let mut farm_metadata = farm_metadata.unwrap();
+        farm_metadata.locked_asset_factory_address = self.locked_asset_factory_address().get();
+
+        self.raw_vm_api().upgrade_from_source_contract(
+            &farm_metadata.address,
+            self.blockchain().get_gas_left(),
+            &BigUint::zero(),
+            &self.farm_template_address().get(),
+            CodeMetadata::UPGRADEABLE | CodeMetadata::PAYABLE,
+            &self.build_farm_init_args(&farm_metadata),
+        );
+        self.farm_map().insert(farming_token_id, farm_metadata);
+        Ok(())
+    }
+
+    fn build_farm_init_args(
+        &self,
+        farm_metadata: &FarmContractMetadata<Self::Api>,
+    ) -> ManagedArgBuffer<Self::Api> {
+        let mut arg_buffer = ManagedArgBuffer::new_empty(self.type_manager());
+        arg_buffer.push_arg(self.blockchain().get_sc_address());
+        arg_buffer.push_arg(&farm_metadata.reward_token_id);
+        arg_buffer.push_arg(&farm_metadata.farming_token_id);
+        arg_buffer.push_arg(&farm_metadata.locked_asset_factory_address);
+        arg_buffer.push_arg(&farm_metadata.division_safety_constant);
+        arg_buffer.push_arg(&farm_metadata.pair_address);
+        arg_buffer.push_arg(self.owner().get());
+        arg_buffer
+    }
+
+    #[only_owner]
+    #[endpoint(setFarmTemplateAddress)]
+    fn set_farm_template_address(&self, address: ManagedAddress) {
+        self.farm_template_address().set(&address);
+    }
+
+    #[only_owner]
+    #[endpoint(setLockedAssetFactoryAddress)]
+    fn set_locked_asset_factory_address(&self, address: ManagedAddress) {
+        self.locked_asset_factory_address().set(&address);
+    }
+
+    #[view(getFarm)]
+    fn get_farm(&self, farming_token_id: TokenIdentifier) -> ManagedAddress {
+        self.farm_map()
+            .get(&farming_token_id)
+            .map(|farm_metadata| farm_metadata.address)
+            .unwrap_or_else(|| self.types().managed_address_zero())
+    }
+
+    #[view(getAllFarmContractMetadata)]
+    fn get_all_farm_contract_metadata(&self) -> MultiResultVec<FarmContractMetadata<Self::Api>> {
+        self.farm_map().values().collect()
+    }
+
+    #[storage_mapper("farm_map")]
+    fn farm_map(&self) -> MapMapper<TokenIdentifier, FarmContractMetadata<Self::Api>>;
+
+    #[view(getFarmTemplateAddress)]
+    #[storage_mapper("farm_template_address")]
+    fn farm_template_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getLockedAssetFactoryAddress)]
+    #[storage_mapper("locked_asset_factory_address")]
+    fn locked_asset_factory_address(&self) -> SingleValueMapper<ManagedAddress>;
+}
@@ -1,14 +1,20 @@
 #![no_std]
 
-use factory::PairTokens;
+use factory::{PairKey, PairTokens};
+use pair::config::ProxyTrait as _;
 
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+mod egld_wrapper;
 mod events;
 mod factory;
+mod farm_factory;
 mod lp_tokens;
-mod pair_manager;
+mod pair_delisting;
+pub mod pair_manager;
+mod pair_upgrade;
+mod path_finder;
 mod state;
 
 const DEFAULT_TOTAL_FEE_PERCENT: u64 = 300;
@@ -18,11 +24,17 @@ const MAX_TOTAL_FEE_PERCENT: u64 = 100_000;
 #[elrond_wasm::contract]
 pub trait Router:
     factory::FactoryModule
+    + egld_wrapper::EgldWrapperModule
     + pair_manager::PairManagerModule
+    + path_finder::PathFinderModule
+    + pair_upgrade::PairUpgradeModule
+    + pair_delisting::PairDelistingModule
+    + farm_factory::FarmFactoryModule
     + lp_tokens::LpTokensModule
     + state::StateModule
     + events::EventsModule
     + token_send::TokenSendModule
+    + deadline::DeadlineModule
 {
     #[init]
     fn init(&self, #[var_args] pair_template_address_opt: OptionalArg<ManagedAddress>) {
@@ -30,6 +42,7 @@ pub trait Router:
         self.pair_creation_enabled().set_if_empty(&false);
 
         self.init_factory(pair_template_address_opt.into_option());
+        self.init_pair_delisting();
         self.owner().set(&self.blockchain().get_caller());
     }
 
@@ -41,6 +54,7 @@ pub trait Router:
         #[var_args] fee_percents: VarArgs<u64>,
     ) -> SCResult<ManagedAddress> {
         require!(self.is_active(), "Not active");
+        self.require_pair_map_migrated()?;
         let owner = self.owner().get();
         let caller = self.blockchain().get_caller();
 
@@ -60,18 +74,16 @@ pub trait Router:
             second_token_id.is_valid_esdt_identifier(),
             "Second Token ID is not a valid esdt token ID"
         );
-        let pair_address = self.get_pair(first_token_id.clone(), second_token_id.clone());
-        require!(
-            pair_address == self.types().managed_address_zero(),
-            "Pair already exists"
-        );
-
         let mut total_fee_percent_requested = DEFAULT_TOTAL_FEE_PERCENT;
         let mut special_fee_percent_requested = DEFAULT_SPECIAL_FEE_PERCENT;
+        let mut amp_factor_requested = 0u64;
         let fee_percents_vec = fee_percents.into_vec();
 
         if caller == owner {
-            require!(fee_percents_vec.len() == 2, "Bad percents length");
+            require!(
+                fee_percents_vec.len() == 2 || fee_percents_vec.len() == 3,
+                "Bad percents length"
+            );
             total_fee_percent_requested = fee_percents_vec[0];
             special_fee_percent_requested = fee_percents_vec[1];
             require!(
@@ -79,14 +91,29 @@ pub trait Router:
                     && total_fee_percent_requested < MAX_TOTAL_FEE_PERCENT,
                 "Bad percents"
             );
+
+            // An optional third argument deploys a stableswap pair with the given amp factor.
+            if fee_percents_vec.len() == 3 {
+                amp_factor_requested = fee_percents_vec[2];
+                require!(amp_factor_requested > 0, "Bad amp factor");
+            }
         }
 
+        // Pairs of the same tokens can coexist, as long as their fee tiers differ.
+        let existing_pair_key = self.get_pair_key(
+            &first_token_id,
+            &second_token_id,
+            total_fee_percent_requested,
+        );
+        require!(existing_pair_key.is_none(), "Pair already exists");
+
         let address = self.create_pair(
             &first_token_id,
             &second_token_id,
             &owner,
             total_fee_percent_requested,
             special_fee_percent_requested,
+            amp_factor_requested,
         )?;
 
         self.emit_create_pair_event(
@@ -100,13 +127,17 @@ pub trait Router:
         Ok(address)
     }
 
+    // Without a fee tier, the pair found by getPair without one is upgraded. A legacy pair is
+    // moved to its fee tier first.
     #[only_owner]
     #[endpoint(upgradePair)]
     fn upgrade_pair_endpoint(
         &self,
         first_token_id: TokenIdentifier,
         second_token_id: TokenIdentifier,
-        #[var_args] fee_percents: VarArgs<u64>,
+        total_fee_percent_requested: u64,
+        special_fee_percent_requested: u64,
+        #[var_args] opt_fee_tier: OptionalArg<u64>,
     ) -> SCResult<()> {
         require!(self.is_active(), "Not active");
 
@@ -119,27 +150,38 @@ pub trait Router:
             second_token_id.is_valid_esdt_identifier(),
             "Second Token ID is not a valid esdt token ID"
         );
-        let pair_address = self.get_pair(first_token_id.clone(), second_token_id.clone());
-        require!(
-            pair_address != self.types().managed_address_zero(),
-            "Pair does not exists"
-        );
+        if let Some(pair_tokens) = self.get_legacy_pair_tokens(&first_token_id, &second_token_id)
+        {
+            self.migrate_legacy_pair(pair_tokens);
+        }
 
-        let fee_percents_vec = fee_percents.into_vec();
-        require!(fee_percents_vec.len() == 2, "Bad percents length");
+        let pair_key = match opt_fee_tier {
+            OptionalArg::Some(fee_tier) => {
+                self.get_pair_key(&first_token_id, &second_token_id, fee_tier)
+            }
+            OptionalArg::None => self.get_default_pair_key(&first_token_id, &second_token_id),
+        };
+        require!(pair_key.is_some(), "Pair does not exists");
+        let pair_key = pair_key.unwrap();
+        let pair_address = self.pair_map().get(&pair_key).unwrap();
 
-        let total_fee_percent_requested = fee_percents_vec[0];
-        let special_fee_percent_requested = fee_percents_vec[1];
         require!(
             total_fee_percent_requested >= special_fee_percent_requested
                 && total_fee_percent_requested < MAX_TOTAL_FEE_PERCENT,
             "Bad percents"
         );
+        if total_fee_percent_requested != pair_key.total_fee_percent {
+            let existing_pair_key = self.get_pair_key(
+                &first_token_id,
+                &second_token_id,
+                total_fee_percent_requested,
+            );
+            require!(existing_pair_key.is_none(), "Pair already exists");
+        }
 
         self.upgrade_pair(
             &pair_address,
-            &first_token_id,
-            &second_token_id,
+            pair_key,
             &self.owner().get(),
             total_fee_percent_requested,
             special_fee_percent_requested,
@@ -147,29 +189,135 @@ pub trait Router:
         Ok(())
     }
 
+    // Moves the pairs recorded before fee tiers to the fee tier of their current total fee
+    // percent, at most max_pairs of them per call. Returns the number of pairs left to move.
+    #[only_owner]
+    #[endpoint(migratePairMap)]
+    fn migrate_pair_map(&self, #[var_args] opt_max_pairs: OptionalArg<usize>) -> usize {
+        let max_pairs = opt_max_pairs.into_option().unwrap_or(usize::MAX);
+        let pair_tokens: Vec<PairTokens<Self::Api>> =
+            self.legacy_pair_map().keys().take(max_pairs).collect();
+
+        for tokens in pair_tokens {
+            self.migrate_legacy_pair(tokens);
+        }
+        self.legacy_pair_map().len()
+    }
+
+    fn migrate_legacy_pair(&self, tokens: PairTokens<Self::Api>) {
+        let pair_address = self.legacy_pair_map().remove(&tokens).unwrap();
+        let total_fee_percent = self.get_total_fee_percent(&pair_address);
+        self.pair_map().insert(
+            PairKey {
+                first_token_id: tokens.first_token_id,
+                second_token_id: tokens.second_token_id,
+                total_fee_percent,
+            },
+            pair_address,
+        );
+    }
+
+    // Without a fee tier, the pair of the default tier is returned, or else the first pair
+    // found for the two tokens, so that callers from before fee tiers keep working.
+    // Legacy pairs are found as well until migratePairMap moves them.
     #[view(getPair)]
     fn get_pair(
         &self,
         first_token_id: TokenIdentifier,
         second_token_id: TokenIdentifier,
+        #[var_args] opt_fee_tier: OptionalArg<u64>,
     ) -> ManagedAddress {
-        let mut address = self
-            .pair_map()
-            .get(&PairTokens {
-                first_token_id: first_token_id.clone(),
-                second_token_id: second_token_id.clone(),
+        let opt_fee_tier = opt_fee_tier.into_option();
+        let pair_key = match opt_fee_tier {
+            Some(fee_tier) => self.get_pair_key(&first_token_id, &second_token_id, fee_tier),
+            None => self.get_default_pair_key(&first_token_id, &second_token_id),
+        };
+        if let Some(pair_key) = pair_key {
+            return self.pair_map().get(&pair_key).unwrap();
+        }
+
+        let legacy_pair_address = self
+            .get_legacy_pair_tokens(&first_token_id, &second_token_id)
+            .map(|pair_tokens| self.legacy_pair_map().get(&pair_tokens).unwrap());
+        match (legacy_pair_address, opt_fee_tier) {
+            (Some(address), None) => address,
+            (Some(address), Some(fee_tier))
+                if self.get_total_fee_percent(&address) == fee_tier =>
+            {
+                address
+            }
+            _ => self.types().managed_address_zero(),
+        }
+    }
+
+    fn get_total_fee_percent(&self, pair_address: &ManagedAddress) -> u64 {
+        self.pair_contract_proxy(pair_address.clone())
+            .total_fee_percent()
+            .execute_on_dest_context()
+    }
+
+    // Lists the total fee percent and the address of every pair of the two tokens.
+    #[view(getPairFeeTiers)]
+    fn get_pair_fee_tiers(
+        &self,
+        first_token_id: TokenIdentifier,
+        second_token_id: TokenIdentifier,
+    ) -> MultiResultVec<MultiResult2<u64, ManagedAddress>> {
+        self.pair_map()
+            .iter()
+            .filter(|(pair_key, _)| {
+                (pair_key.first_token_id == first_token_id
+                    && pair_key.second_token_id == second_token_id)
+                    || (pair_key.first_token_id == second_token_id
+                        && pair_key.second_token_id == first_token_id)
             })
-            .unwrap_or_else(|| self.types().managed_address_zero());
-
-        if address == self.types().managed_address_zero() {
-            address = self
-                .pair_map()
-                .get(&PairTokens {
-                    first_token_id: second_token_id,
-                    second_token_id: first_token_id,
-                })
-                .unwrap_or_else(|| self.types().managed_address_zero());
+            .map(|(pair_key, address)| (pair_key.total_fee_percent, address).into())
+            .collect()
+    }
+
+    fn get_default_pair_key(
+        &self,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+    ) -> Option<PairKey<Self::Api>> {
+        let pair_key =
+            self.get_pair_key(first_token_id, second_token_id, DEFAULT_TOTAL_FEE_PERCENT);
+        if pair_key.is_some() {
+            return pair_key;
+        }
+
+        self.pair_map().keys().find(|pair_key| {
+            (&pair_key.first_token_id == first_token_id
+                && &pair_key.second_token_id == second_token_id)
+                || (&pair_key.first_token_id == second_token_id
+                    && &pair_key.second_token_id == first_token_id)
+        })
+    }
+
+    // The tokens of a pair can be given in either order.
+    fn get_pair_key(
+        &self,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+        fee_tier: u64,
+    ) -> Option<PairKey<Self::Api>> {
+        let pair_key = PairKey {
+            first_token_id: first_token_id.clone(),
+            second_token_id: second_token_id.clone(),
+            total_fee_percent: fee_tier,
+        };
+        if self.pair_map().contains_key(&pair_key) {
+            return Some(pair_key);
+        }
+
+        let pair_key = PairKey {
+            first_token_id: second_token_id.clone(),
+            second_token_id: first_token_id.clone(),
+            total_fee_percent: fee_tier,
+        };
+        if self.pair_map().contains_key(&pair_key) {
+            return Some(pair_key);
         }
-        address
+        None
     }
 }
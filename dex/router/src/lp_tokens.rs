@@ -1,19 +1,30 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+use super::egld_wrapper;
+use super::events;
 use super::factory;
 use super::pair_manager;
 use super::state;
 
+use deadline::NO_DEADLINE;
+use factory::PairSetupStage;
+use pair::config::ProxyTrait as _;
+use pair::ProxyTrait as _;
+
 const LP_TOKEN_DECIMALS: usize = 18;
 const LP_TOKEN_INITIAL_SUPPLY: u64 = 1000;
+const ACCEPT_PAY_FUNC_NAME: &[u8] = b"acceptPay";
 
 #[elrond_wasm::module]
 pub trait LpTokensModule:
     pair_manager::PairManagerModule
     + state::StateModule
     + factory::FactoryModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
     + token_send::TokenSendModule
+    + deadline::DeadlineModule
 {
     #[payable("EGLD")]
     #[endpoint(issueLpToken)]
@@ -42,11 +53,35 @@ pub trait LpTokensModule:
             }
         };
 
-        let result = self.get_lp_token_for_pair(&pair_address);
+        self.require_lp_token_can_be_issued(&pair_address)?;
+
+        self.pair_lp_token_info(&pair_address)
+            .set(&(tp_token_display_name, tp_token_ticker));
+        Ok(self.issue_lp_token_async(&caller, &pair_address, issue_cost))
+    }
+
+    // The issue is only known to have failed or succeeded in its callback, so a second
+    // one is rejected until then.
+    fn require_lp_token_can_be_issued(&self, pair_address: &ManagedAddress) -> SCResult<()> {
+        let result = self.get_lp_token_for_pair(pair_address);
         require!(result.is_egld(), "LP Token already issued");
+        require!(
+            !self.lp_token_issue_in_progress(pair_address).get(),
+            "LP Token issue in progress"
+        );
+        Ok(())
+    }
 
-        Ok(self
-            .send()
+    fn issue_lp_token_async(
+        &self,
+        caller: &ManagedAddress,
+        pair_address: &ManagedAddress,
+        issue_cost: BigUint,
+    ) -> AsyncCall {
+        let (tp_token_display_name, tp_token_ticker) = self.pair_lp_token_info(pair_address).get();
+        self.lp_token_issue_in_progress(pair_address).set(&true);
+
+        self.send()
             .esdt_system_sc_proxy()
             .issue_fungible(
                 issue_cost,
@@ -68,8 +103,8 @@ pub trait LpTokensModule:
             .async_call()
             .with_callback(
                 self.callbacks()
-                    .lp_token_issue_callback(&caller, &pair_address),
-            ))
+                    .lp_token_issue_callback(caller, pair_address),
+            )
     }
 
     #[endpoint(setLocalRoles)]
@@ -80,18 +115,142 @@ pub trait LpTokensModule:
         let pair_token = self.get_lp_token_for_pair(&pair_address);
         require!(pair_token.is_esdt(), "LP token not issued");
 
-        Ok(self
-            .send()
+        Ok(self.set_local_roles_async(&pair_address, &pair_token))
+    }
+
+    fn set_local_roles_async(
+        &self,
+        pair_address: &ManagedAddress,
+        pair_token: &TokenIdentifier,
+    ) -> AsyncCall {
+        self.send()
             .esdt_system_sc_proxy()
             .set_special_roles(
-                &pair_address,
-                &pair_token,
+                pair_address,
+                pair_token,
                 (&[EsdtLocalRole::Mint, EsdtLocalRole::Burn][..])
                     .into_iter()
                     .cloned(),
             )
             .async_call()
-            .with_callback(self.callbacks().change_roles_callback()))
+            .with_callback(self.callbacks().pair_local_roles_callback(pair_address))
+    }
+
+    // Runs the next stage of the pair setup, which is also the one that failed, if any:
+    // issuing the LP token (paid in EGLD, with the name and ticker given to issueLpToken),
+    // setting its local roles, then activating the pair. The activation can seed the
+    // initial liquidity, if paid with both tokens of the pair, and sends the LP tokens back.
+    #[payable("*")]
+    #[endpoint(continuePairSetup)]
+    fn continue_pair_setup(
+        &self,
+        pair_address: ManagedAddress,
+    ) -> SCResult<OptionalResult<AsyncCall>> {
+        require!(self.is_active(), "Not active");
+        self.check_is_pair_sc(&pair_address)?;
+        let caller = self.blockchain().get_caller();
+        require!(
+            caller == self.owner().get() || caller == self.pair_creator(&pair_address).get(),
+            "Permission denied"
+        );
+        require!(
+            !self.deprecated_pairs().contains_key(&pair_address),
+            "Pair is deprecated"
+        );
+
+        let egld_amount = self.call_value().egld_value();
+        let payments = self.get_all_payments();
+        match self.get_pair_setup_stage(&pair_address) {
+            PairSetupStage::Deployed => {
+                require!(
+                    !self.pair_lp_token_info(&pair_address).is_empty(),
+                    "LP token name and ticker not set"
+                );
+                require!(egld_amount > 0, "LP token issue cost not paid");
+                self.require_lp_token_can_be_issued(&pair_address)?;
+                Ok(OptionalResult::Some(self.issue_lp_token_async(
+                    &caller,
+                    &pair_address,
+                    egld_amount,
+                )))
+            }
+            PairSetupStage::LpTokenIssued => {
+                require!(
+                    egld_amount == 0 && payments.is_empty(),
+                    "No payment expected"
+                );
+                let pair_token = self.get_lp_token_for_pair(&pair_address);
+                Ok(OptionalResult::Some(
+                    self.set_local_roles_async(&pair_address, &pair_token),
+                ))
+            }
+            PairSetupStage::LocalRolesSet => {
+                require!(egld_amount == 0, "EGLD not accepted");
+                self.activate_pair(&caller, &pair_address, payments)?;
+                Ok(OptionalResult::None)
+            }
+            PairSetupStage::Active => sc_error!("Pair setup already complete"),
+        }
+    }
+
+    fn activate_pair(
+        &self,
+        caller: &ManagedAddress,
+        pair_address: &ManagedAddress,
+        payments: Vec<EsdtTokenPayment<Self::Api>>,
+    ) -> SCResult<()> {
+        require!(
+            payments.is_empty() || payments.len() == 2,
+            "Initial liquidity should be paid with both tokens"
+        );
+
+        if !payments.is_empty() {
+            let first_token_amount_min = payments[0].amount.clone();
+            let second_token_amount_min = payments[1].amount.clone();
+            let mut all_token_payments = ManagedVec::new(self.type_manager());
+            for payment in payments.into_iter() {
+                all_token_payments.push(payment);
+            }
+
+            let (lp_payment, _, _) = self
+                .pair_contract_proxy(pair_address.clone())
+                .add_liquidity(
+                    first_token_amount_min,
+                    second_token_amount_min,
+                    OptionalArg::Some(NO_DEADLINE),
+                    OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
+                )
+                .with_multi_token_transfer(all_token_payments)
+                .execute_on_dest_context_custom_range(|_, after| (after - 3, after))
+                .into_tuple();
+            self.send().direct(
+                caller,
+                &lp_payment.token_identifier,
+                0,
+                &lp_payment.amount,
+                &[],
+            );
+        }
+
+        self.pair_contract_proxy(pair_address.clone())
+            .resume()
+            .execute_on_dest_context();
+        self.pair_setup_stage(pair_address)
+            .set(&PairSetupStage::Active);
+        Ok(())
+    }
+
+    #[view(getPairSetupStatus)]
+    fn get_pair_setup_status(
+        &self,
+        pair_address: ManagedAddress,
+    ) -> SCResult<MultiResult2<PairSetupStage, BoxedBytes>> {
+        self.check_is_pair_sc(&pair_address)?;
+        Ok((
+            self.get_pair_setup_stage(&pair_address),
+            self.pair_setup_error(&pair_address).get(),
+        )
+            .into())
     }
 
     #[only_owner]
@@ -120,16 +279,19 @@ pub trait LpTokensModule:
         #[payment_amount] returned_tokens: BigUint,
         #[call_result] result: AsyncCallResult<()>,
     ) {
+        self.lp_token_issue_in_progress(address).clear();
         match result {
             AsyncCallResult::Ok(()) => {
                 self.pair_temporary_owner().remove(address);
                 self.set_lp_token_for_pair(address, &token_id);
+                self.advance_pair_setup(address, PairSetupStage::LpTokenIssued);
             }
             AsyncCallResult::Err(message) => {
                 self.last_error_message().set(&message.err_msg);
+                self.pair_setup_error(address).set(&message.err_msg);
 
                 if token_id.is_egld() && returned_tokens > 0 {
-                    let _ = self.send().direct_egld(caller, &returned_tokens, &[]);
+                    self.send().direct_egld(caller, &returned_tokens, &[]);
                 }
             }
         }
@@ -146,4 +308,31 @@ pub trait LpTokensModule:
             }
         }
     }
+
+    #[callback]
+    fn pair_local_roles_callback(
+        &self,
+        pair_address: &ManagedAddress,
+        #[call_result] result: AsyncCallResult<()>,
+    ) {
+        match result {
+            AsyncCallResult::Ok(()) => {
+                self.last_error_message().clear();
+                self.advance_pair_setup(pair_address, PairSetupStage::LocalRolesSet);
+            }
+            AsyncCallResult::Err(message) => {
+                self.last_error_message().set(&message.err_msg);
+                self.pair_setup_error(pair_address).set(&message.err_msg);
+            }
+        }
+    }
+
+    // Stages are only ever moved forward, so that a repeated action does not undo a later one.
+    fn advance_pair_setup(&self, pair_address: &ManagedAddress, stage: PairSetupStage) {
+        let current_stage = self.get_pair_setup_stage(pair_address);
+        if (current_stage as u8) < (stage as u8) {
+            self.pair_setup_stage(pair_address).set(&stage);
+        }
+        self.pair_setup_error(pair_address).clear();
+    }
 }
@@ -0,0 +1,125 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::egld_wrapper;
+use super::events;
+use super::factory;
+use super::farm_factory;
+use super::pair_manager;
+use super::state;
+
+use factory::{DeprecatedPair, PairKey};
+use pair::config::ProxyTrait as _;
+
+const DEFAULT_DELISTING_GRACE_PERIOD_BLOCKS: u64 = 100_800;
+
+#[elrond_wasm::module]
+pub trait PairDelistingModule:
+    state::StateModule
+    + factory::FactoryModule
+    + pair_manager::PairManagerModule
+    + farm_factory::FarmFactoryModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + deadline::DeadlineModule
+{
+    fn init_pair_delisting(&self) {
+        self.delisting_grace_period()
+            .set_if_empty(&DEFAULT_DELISTING_GRACE_PERIOD_BLOCKS);
+    }
+
+    // Stops the swaps of the pair, while still letting liquidity providers exit, and allows
+    // its removal once the grace period has passed.
+    #[only_owner]
+    #[endpoint(deprecatePair)]
+    fn deprecate_pair(&self, pair_address: ManagedAddress) -> SCResult<()> {
+        require!(self.is_active(), "Not active");
+        require!(
+            !self.deprecated_pairs().contains_key(&pair_address),
+            "Pair already deprecated"
+        );
+        let pair_key = self.get_pair_key_by_address(&pair_address);
+        require!(pair_key.is_some(), "Not a pair SC");
+        let pair_key = pair_key.unwrap();
+
+        self.pair_contract_proxy(pair_address.clone())
+            .set_state_active_no_swaps()
+            .execute_on_dest_context();
+
+        let removal_block =
+            self.blockchain().get_block_nonce() + self.delisting_grace_period().get();
+        self.deprecated_pairs().insert(
+            pair_address.clone(),
+            DeprecatedPair {
+                first_token_id: pair_key.first_token_id,
+                second_token_id: pair_key.second_token_id,
+                total_fee_percent: pair_key.total_fee_percent,
+                address: pair_address,
+                removal_block,
+                removed: false,
+            },
+        );
+        Ok(())
+    }
+
+    // Removes a deprecated pair from the Router, along with what the Router kept about its setup
+    // and the farm of its LP token. The pair and farm contracts themselves are left untouched.
+    #[only_owner]
+    #[endpoint(removeDeprecatedPair)]
+    fn remove_deprecated_pair(&self, pair_address: ManagedAddress) -> SCResult<()> {
+        require!(self.is_active(), "Not active");
+        let deprecated_pair = self.deprecated_pairs().get(&pair_address);
+        require!(deprecated_pair.is_some(), "Pair not deprecated");
+        let mut deprecated_pair = deprecated_pair.unwrap();
+        require!(!deprecated_pair.removed, "Pair already removed");
+        require!(
+            self.blockchain().get_block_nonce() >= deprecated_pair.removal_block,
+            "Grace period not over"
+        );
+
+        let pair_key = PairKey {
+            first_token_id: deprecated_pair.first_token_id.clone(),
+            second_token_id: deprecated_pair.second_token_id.clone(),
+            total_fee_percent: deprecated_pair.total_fee_percent,
+        };
+        let lp_token_id = self.get_lp_token_for_pair(&pair_address);
+        if lp_token_id.is_esdt() {
+            self.farm_map().remove(&lp_token_id);
+        }
+        self.remove_pair_from_map(&pair_key);
+
+        self.pair_temporary_owner().remove(&pair_address);
+        self.pair_setup_stage(&pair_address).clear();
+        self.pair_setup_error(&pair_address).clear();
+        self.pair_creator(&pair_address).clear();
+        self.pair_lp_token_info(&pair_address).clear();
+
+        deprecated_pair.removed = true;
+        self.deprecated_pairs()
+            .insert(pair_address, deprecated_pair);
+        Ok(())
+    }
+
+    fn get_pair_key_by_address(&self, pair_address: &ManagedAddress) -> Option<PairKey<Self::Api>> {
+        self.pair_map()
+            .iter()
+            .find(|(_, address)| address == pair_address)
+            .map(|(pair_key, _)| pair_key)
+    }
+
+    #[only_owner]
+    #[endpoint(setDelistingGracePeriod)]
+    fn set_delisting_grace_period(&self, period_blocks: u64) {
+        self.delisting_grace_period().set(&period_blocks);
+    }
+
+    #[view(getDeprecatedPairs)]
+    fn get_deprecated_pairs(&self) -> MultiResultVec<DeprecatedPair<Self::Api>> {
+        self.deprecated_pairs().values().collect()
+    }
+
+    #[view(getDelistingGracePeriod)]
+    #[storage_mapper("delisting_grace_period")]
+    fn delisting_grace_period(&self) -> SingleValueMapper<u64>;
+}
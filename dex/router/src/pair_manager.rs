@@ -3,26 +3,50 @@ elrond_wasm::derive_imports!();
 
 type Nonce = u64;
 
+use super::egld_wrapper;
+use super::events;
 use super::factory;
 use super::state;
 
-type SwapOperationType<ManagedTypeApi> = MultiArg4<
+pub type SwapOperationType<ManagedTypeApi> = MultiArg4<
     ManagedAddress<ManagedTypeApi>,
     BoxedBytes,
     TokenIdentifier<ManagedTypeApi>,
     BigUint<ManagedTypeApi>,
 >;
 
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct SwapOperation<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub function: BoxedBytes,
+    pub token_wanted: TokenIdentifier<M>,
+    pub amount_wanted: BigUint<M>,
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct SwapPath<M: ManagedTypeApi> {
+    pub share: u64,
+    pub operations: Vec<SwapOperation<M>>,
+}
+
+const MAX_PATH_SHARE: u64 = 10_000;
 const ACCEPT_PAY_FUNC_NAME: &[u8] = b"acceptPay";
 const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
 const SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME: &[u8] = b"swapTokensFixedOutput";
 
+use deadline::NO_DEADLINE;
+use events::SwapHop;
 use pair::config::ProxyTrait as _;
 use pair::fee::ProxyTrait as _;
 
 #[elrond_wasm::module]
 pub trait PairManagerModule:
-    state::StateModule + factory::FactoryModule + token_send::TokenSendModule
+    state::StateModule
+    + factory::FactoryModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + deadline::DeadlineModule
 {
     #[only_owner]
     #[endpoint(setFeeOn)]
@@ -31,12 +55,13 @@ pub trait PairManagerModule:
         pair_address: ManagedAddress,
         fee_to_address: ManagedAddress,
         fee_token: TokenIdentifier,
+        #[var_args] opt_fee_share: OptionalArg<u64>,
     ) -> SCResult<()> {
         require!(self.is_active(), "Not active");
         self.check_is_pair_sc(&pair_address)?;
 
         self.pair_contract_proxy(pair_address)
-            .set_fee_on(true, fee_to_address, fee_token)
+            .set_fee_on(true, fee_to_address, fee_token, opt_fee_share)
             .execute_on_dest_context();
 
         Ok(())
@@ -54,7 +79,24 @@ pub trait PairManagerModule:
         self.check_is_pair_sc(&pair_address)?;
 
         self.pair_contract_proxy(pair_address)
-            .set_fee_on(false, fee_to_address, fee_token)
+            .set_fee_on(false, fee_to_address, fee_token, OptionalArg::None)
+            .execute_on_dest_context();
+
+        Ok(())
+    }
+
+    #[only_owner]
+    #[endpoint(setFeeDestinationShares)]
+    fn set_fee_destination_shares(
+        &self,
+        pair_address: ManagedAddress,
+        #[var_args] fee_shares: MultiArgVec<MultiArg2<ManagedAddress, u64>>,
+    ) -> SCResult<()> {
+        require!(self.is_active(), "Not active");
+        self.check_is_pair_sc(&pair_address)?;
+
+        self.pair_contract_proxy(pair_address)
+            .set_fee_destination_shares(fee_shares)
             .execute_on_dest_context();
 
         Ok(())
@@ -64,6 +106,9 @@ pub trait PairManagerModule:
     #[endpoint(acceptPay)]
     fn accept_pay(&self) {}
 
+    // Returns all the payments sent back: the residuums of fixed output swaps and the final output.
+    // The deadline is a block timestamp, or NO_DEADLINE. It comes before the swap operations,
+    // since they take all the arguments that follow them.
     #[payable("*")]
     #[endpoint(multiPairSwap)]
     fn multi_pair_swap(
@@ -71,9 +116,31 @@ pub trait PairManagerModule:
         #[payment_token] token_id: TokenIdentifier,
         #[payment_amount] amount: BigUint,
         #[payment_nonce] nonce: Nonce,
+        min_final_amount: BigUint,
+        deadline: u64,
         swap_operations: MultiArgVec<SwapOperationType<Self::Api>>,
         #[var_args] opt_accept_funds_func: OptionalArg<BoxedBytes>,
-    ) -> SCResult<()> {
+    ) -> SCResult<MultiResultVec<EsdtTokenPayment<Self::Api>>> {
+        self.require_deadline_not_passed(&OptionalArg::Some(deadline))?;
+        self.perform_multi_pair_swap(
+            token_id,
+            amount,
+            nonce,
+            min_final_amount,
+            swap_operations,
+            opt_accept_funds_func,
+        )
+    }
+
+    fn perform_multi_pair_swap(
+        &self,
+        token_id: TokenIdentifier,
+        amount: BigUint,
+        nonce: Nonce,
+        min_final_amount: BigUint,
+        swap_operations: MultiArgVec<SwapOperationType<Self::Api>>,
+        opt_accept_funds_func: OptionalArg<BoxedBytes>,
+    ) -> SCResult<MultiResultVec<EsdtTokenPayment<Self::Api>>> {
         require!(nonce == 0, "Invalid nonce. Should be zero");
         require!(amount > 0, "Invalid amount. Should not be zero");
         require!(
@@ -82,40 +149,278 @@ pub trait PairManagerModule:
         );
 
         let caller = self.blockchain().get_caller();
-        let mut payments = Vec::new();
-        let mut last_payment = self.create_payment(&token_id, nonce, &amount);
-
-        for entry in swap_operations.into_vec() {
-            let (pair_address, function, token_wanted, amount_wanted) = entry.into_tuple();
-            self.check_is_pair_sc(&pair_address)?;
-
-            if function == BoxedBytes::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME) {
-                last_payment = self.actual_swap_fixed_input(
-                    pair_address,
-                    last_payment.token_identifier.clone(),
-                    last_payment.amount,
-                    token_wanted,
-                    amount_wanted,
+        let swap_operations = swap_operations.into_vec();
+        let last_operation_index = swap_operations.len() - 1;
+        let is_egld_in = token_id.is_egld();
+        let mut is_egld_out = false;
+        if is_egld_in {
+            self.require_egld_wrapper_set()?;
+        }
+
+        // The whole path is checked before the first swap, so that a broken chain
+        // does not fail halfway, after some of the pairs were already called.
+        let mut operations = Vec::new();
+        let mut token_in = if is_egld_in {
+            self.wrapped_egld_token_id().get()
+        } else {
+            token_id.clone()
+        };
+        for (index, entry) in swap_operations.into_iter().enumerate() {
+            let (pair_address, function, mut token_wanted, amount_wanted) = entry.into_tuple();
+
+            if token_wanted.is_egld() {
+                require!(
+                    index == last_operation_index,
+                    "EGLD can only be the last token wanted"
                 );
-            } else if function == BoxedBytes::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME) {
-                let (payment, residuum) = self.actual_swap_fixed_output(
-                    pair_address,
-                    last_payment.token_identifier,
-                    last_payment.amount,
-                    token_wanted,
-                    amount_wanted,
+                self.require_egld_wrapper_set()?;
+                token_wanted = self.wrapped_egld_token_id().get();
+                is_egld_out = true;
+            }
+            self.check_is_valid_swap_operation(&pair_address, &token_in, &token_wanted)?;
+
+            token_in = token_wanted.clone();
+            operations.push((pair_address, function, token_wanted, amount_wanted));
+        }
+
+        let mut payments = Vec::new();
+        let mut hops = Vec::new();
+        let mut last_payment = if is_egld_in {
+            self.wrap_egld(&amount)
+        } else {
+            self.create_payment(&token_id, nonce, &amount)
+        };
+
+        for (pair_address, function, token_wanted, amount_wanted) in operations.into_iter() {
+            last_payment = self.perform_swap_hop(
+                pair_address,
+                &function,
+                last_payment,
+                token_wanted,
+                amount_wanted,
+                &mut payments,
+                &mut hops,
+            )?;
+        }
+        require!(
+            last_payment.amount >= min_final_amount,
+            "Final amount lesser than minimum final amount"
+        );
+        let final_amount = last_payment.amount.clone();
+
+        // Wrapped EGLD residuums of an EGLD payment go back as EGLD, as does an EGLD output.
+        let mut egld_amount_out = BigUint::zero();
+        if is_egld_in {
+            let wrapped_egld_token_id = self.wrapped_egld_token_id().get();
+            for residuum in payments.iter() {
+                if residuum.token_identifier == wrapped_egld_token_id {
+                    egld_amount_out += &residuum.amount;
+                }
+            }
+            payments.retain(|residuum| residuum.token_identifier != wrapped_egld_token_id);
+        }
+        let token_out = if is_egld_out {
+            egld_amount_out += &last_payment.amount;
+            TokenIdentifier::egld()
+        } else {
+            let token_out = last_payment.token_identifier.clone();
+            payments.push(last_payment);
+            token_out
+        };
+
+        self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
+        self.transfer_egld_from_wrapped(&caller, &egld_amount_out, &opt_accept_funds_func)?;
+
+        payments.retain(|payment| payment.amount > 0);
+        if egld_amount_out > 0 {
+            payments.push(self.create_payment(&TokenIdentifier::egld(), 0, &egld_amount_out));
+        }
+
+        self.emit_multi_pair_swap_event(caller, token_id, amount, token_out, final_amount, hops);
+        Ok(payments.into())
+    }
+
+    // Splits the payment between independent paths, by their shares in basis points, and
+    // sends the merged output back. Every path has to end with the same token.
+    // Returns all the payments sent back, the same as multiPairSwap.
+    #[payable("*")]
+    #[endpoint(multiPathSwap)]
+    fn multi_path_swap(
+        &self,
+        #[payment_token] token_id: TokenIdentifier,
+        #[payment_amount] amount: BigUint,
+        #[payment_nonce] nonce: Nonce,
+        min_amount_out: BigUint,
+        #[var_args] swap_paths: MultiArgVec<SwapPath<Self::Api>>,
+    ) -> SCResult<MultiResultVec<EsdtTokenPayment<Self::Api>>> {
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount > 0, "Invalid amount. Should not be zero");
+        require!(
+            !token_id.is_egld(),
+            "EGLD is not supported for multi path swaps"
+        );
+        require!(
+            !swap_paths.is_empty(),
+            "Invalid swap paths. Should not be empty"
+        );
+
+        let swap_paths = swap_paths.into_vec();
+        let mut total_share = 0u64;
+        for swap_path in swap_paths.iter() {
+            require!(swap_path.share != 0, "Path share cannot be zero");
+            require!(
+                !swap_path.operations.is_empty(),
+                "Invalid swap operations chain. Should not be empty"
+            );
+            let mut token_in = &token_id;
+            for operation in swap_path.operations.iter() {
+                require!(
+                    !operation.token_wanted.is_egld(),
+                    "EGLD is not supported for multi path swaps"
                 );
-                last_payment = payment;
-                payments.push(residuum);
+                self.check_is_valid_swap_operation(
+                    &operation.pair_address,
+                    token_in,
+                    &operation.token_wanted,
+                )?;
+                token_in = &operation.token_wanted;
+            }
+            total_share += swap_path.share;
+        }
+        require!(
+            total_share == MAX_PATH_SHARE,
+            "Path shares should add up to 100%"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let last_path_index = swap_paths.len() - 1;
+        let mut amount_left = amount.clone();
+        let mut payments = Vec::new();
+        let mut hops = Vec::new();
+        let mut token_out = None;
+        let mut amount_out = BigUint::zero();
+
+        for (index, swap_path) in swap_paths.into_iter().enumerate() {
+            // The last path takes what is left, so that no rounding dust stays behind.
+            let path_amount = if index == last_path_index {
+                amount_left.clone()
             } else {
-                return sc_error!("Invalid function to call");
+                &amount * swap_path.share / MAX_PATH_SHARE
+            };
+            amount_left -= &path_amount;
+            if path_amount == 0 {
+                continue;
             }
+
+            let mut last_payment = self.create_payment(&token_id, 0, &path_amount);
+            for operation in swap_path.operations.into_iter() {
+                last_payment = self.perform_swap_hop(
+                    operation.pair_address,
+                    &operation.function,
+                    last_payment,
+                    operation.token_wanted,
+                    operation.amount_wanted,
+                    &mut payments,
+                    &mut hops,
+                )?;
+            }
+
+            if let Some(token_out) = &token_out {
+                require!(
+                    token_out == &last_payment.token_identifier,
+                    "All paths should end with the same token"
+                );
+            }
+            amount_out += &last_payment.amount;
+            token_out = Some(last_payment.token_identifier);
         }
+        require!(
+            amount_out >= min_amount_out,
+            "Computed amount out lesser than minimum amount out"
+        );
 
-        payments.push(last_payment);
-        self.send_multiple_tokens_compact(&caller, &payments, &opt_accept_funds_func)?;
+        let token_out = match token_out {
+            Some(token_out) => token_out,
+            None => return sc_error!("No path was taken"),
+        };
+        payments.push(self.create_payment(&token_out, 0, &amount_out));
+        self.send_multiple_tokens_compact(&caller, &payments, &OptionalArg::None)?;
 
-        Ok(())
+        payments.retain(|payment| payment.amount > 0);
+        self.emit_multi_pair_swap_event(caller, token_id, amount, token_out, amount_out, hops);
+        Ok(payments.into())
+    }
+
+    // Performs one swap operation and records it as a hop of the swap event.
+    #[allow(clippy::too_many_arguments)]
+    fn perform_swap_hop(
+        &self,
+        pair_address: ManagedAddress,
+        function: &BoxedBytes,
+        last_payment: EsdtTokenPayment<Self::Api>,
+        token_wanted: TokenIdentifier,
+        amount_wanted: BigUint,
+        payments: &mut Vec<EsdtTokenPayment<Self::Api>>,
+        hops: &mut Vec<SwapHop<Self::Api>>,
+    ) -> SCResult<EsdtTokenPayment<Self::Api>> {
+        let hop_token_in = last_payment.token_identifier.clone();
+        let mut hop_amount_in = last_payment.amount.clone();
+        let payments_before = payments.len();
+
+        let payment = self.perform_swap_operation(
+            pair_address.clone(),
+            function,
+            last_payment,
+            token_wanted,
+            amount_wanted,
+            payments,
+        )?;
+
+        if payments.len() > payments_before {
+            hop_amount_in -= &payments[payments.len() - 1].amount;
+        }
+        hops.push(SwapHop {
+            pair_address,
+            token_in: hop_token_in,
+            amount_in: hop_amount_in,
+            token_out: payment.token_identifier.clone(),
+            amount_out: payment.amount.clone(),
+        });
+        Ok(payment)
+    }
+
+    // Residuums of fixed output swaps are added to the payments to send back.
+    // The operation is expected to be validated with check_is_valid_swap_operation.
+    fn perform_swap_operation(
+        &self,
+        pair_address: ManagedAddress,
+        function: &BoxedBytes,
+        last_payment: EsdtTokenPayment<Self::Api>,
+        token_wanted: TokenIdentifier,
+        amount_wanted: BigUint,
+        payments: &mut Vec<EsdtTokenPayment<Self::Api>>,
+    ) -> SCResult<EsdtTokenPayment<Self::Api>> {
+        if function == &BoxedBytes::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME) {
+            Ok(self.actual_swap_fixed_input(
+                pair_address,
+                last_payment.token_identifier,
+                last_payment.amount,
+                token_wanted,
+                amount_wanted,
+            ))
+        } else if function == &BoxedBytes::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME) {
+            let (payment, residuum) = self.actual_swap_fixed_output(
+                pair_address,
+                last_payment.token_identifier,
+                last_payment.amount,
+                token_wanted,
+                amount_wanted,
+            );
+            payments.push(residuum);
+            Ok(payment)
+        } else {
+            sc_error!("Invalid function to call")
+        }
     }
 
     fn actual_swap_fixed_input(
@@ -132,6 +437,7 @@ pub trait PairManagerModule:
                 amount_in,
                 token_out,
                 amount_out_min,
+                OptionalArg::Some(NO_DEADLINE),
                 OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
             )
             .execute_on_dest_context_custom_range(|_, after| (after - 1, after))
@@ -151,6 +457,7 @@ pub trait PairManagerModule:
                 amount_in_max,
                 token_out,
                 amount_out,
+                OptionalArg::Some(NO_DEADLINE),
                 OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
             )
             .execute_on_dest_context_custom_range(|_, after| (after - 2, after))
@@ -178,6 +485,10 @@ pub trait PairManagerModule:
             self.state().set(&true);
         } else {
             self.check_is_pair_sc(&address)?;
+            require!(
+                !self.deprecated_pairs().contains_key(&address),
+                "Pair is deprecated"
+            );
             self.pair_contract_proxy(address)
                 .resume()
                 .execute_on_dest_context();
@@ -211,14 +522,33 @@ pub trait PairManagerModule:
 
     fn check_is_pair_sc(&self, pair_address: &ManagedAddress) -> SCResult<()> {
         require!(
-            self.pair_map()
-                .values()
-                .any(|address| &address == pair_address),
+            self.get_pair_tokens_by_address(pair_address).is_some(),
             "Not a pair SC"
         );
         Ok(())
     }
 
+    // The pair has to be registered and hold both the token paid and the token wanted.
+    fn check_is_valid_swap_operation(
+        &self,
+        pair_address: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        token_wanted: &TokenIdentifier,
+    ) -> SCResult<()> {
+        let pair_tokens = self.get_pair_tokens_by_address(pair_address);
+        require!(pair_tokens.is_some(), "Not a pair SC");
+
+        let pair_tokens = pair_tokens.unwrap();
+        let is_pair_token = |token: &TokenIdentifier| {
+            token == &pair_tokens.first_token_id || token == &pair_tokens.second_token_id
+        };
+        require!(
+            token_in != token_wanted && is_pair_token(token_in) && is_pair_token(token_wanted),
+            "Invalid swap path"
+        );
+        Ok(())
+    }
+
     #[proxy]
     fn pair_contract_proxy(&self, to: ManagedAddress) -> pair::Proxy<Self::Api>;
 }
@@ -0,0 +1,115 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::egld_wrapper;
+use super::events;
+use super::factory;
+use super::pair_manager;
+use super::state;
+
+use pair::config::ProxyTrait as _;
+
+// Left for reading the fees of a pair and for saving the progress at the end of a chunk.
+const UPGRADE_ALL_PAIRS_GAS_RESERVE: u64 = 10_000_000;
+
+#[elrond_wasm::module]
+pub trait PairUpgradeModule:
+    state::StateModule
+    + factory::FactoryModule
+    + pair_manager::PairManagerModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + deadline::DeadlineModule
+{
+    // Upgrades the pairs to the current template, in pair_map order, keeping their fee percents.
+    // Stops when the gas left no longer covers another pair and resumes after the last pair
+    // upgraded on the next call. Progress is reset once the last pair has been upgraded.
+    #[only_owner]
+    #[endpoint(upgradeAllPairs)]
+    fn upgrade_all_pairs(
+        &self,
+        gas_per_pair: u64,
+        #[var_args] opt_max_pairs: OptionalArg<usize>,
+    ) -> SCResult<()> {
+        require!(self.is_active(), "Not active");
+        require!(
+            !self.pair_template_address().is_empty(),
+            "pair contract template is empty"
+        );
+        require!(gas_per_pair != 0, "Gas per pair cannot be zero");
+        require!(
+            self.blockchain().get_gas_left() > gas_per_pair + UPGRADE_ALL_PAIRS_GAS_RESERVE,
+            "Not enough gas"
+        );
+        let max_pairs = opt_max_pairs.into_option().unwrap_or(usize::MAX);
+        require!(max_pairs != 0, "Max pairs cannot be zero");
+
+        let owner = self.owner().get();
+        let total_pairs = self.pair_map().len();
+        let start_index = self.get_pair_upgrade_start_index();
+        let mut next_index = start_index;
+        let mut last_upgraded_pair = None;
+
+        for (pair_key, pair_address) in self.pair_map().iter().skip(start_index) {
+            if next_index - start_index == max_pairs
+                || self.blockchain().get_gas_left() <= gas_per_pair + UPGRADE_ALL_PAIRS_GAS_RESERVE
+            {
+                break;
+            }
+
+            let total_fee_percent: u64 = self
+                .pair_contract_proxy(pair_address.clone())
+                .total_fee_percent()
+                .execute_on_dest_context();
+            let special_fee_percent: u64 = self
+                .pair_contract_proxy(pair_address.clone())
+                .special_fee_percent()
+                .execute_on_dest_context();
+            self.upgrade_pair_contract(
+                &pair_address,
+                &pair_key,
+                &owner,
+                total_fee_percent,
+                special_fee_percent,
+                gas_per_pair,
+            );
+            next_index += 1;
+            last_upgraded_pair = Some(pair_address);
+        }
+
+        let completed = next_index >= total_pairs;
+        if completed {
+            self.last_upgraded_pair().clear();
+        } else if let Some(pair_address) = last_upgraded_pair {
+            self.last_upgraded_pair().set(&pair_address);
+        }
+
+        self.emit_upgrade_all_pairs_event(
+            self.blockchain().get_caller(),
+            start_index,
+            next_index,
+            total_pairs,
+            completed,
+        );
+        Ok(())
+    }
+
+    // The position in pair_map following the last pair upgraded in the current run.
+    fn get_pair_upgrade_start_index(&self) -> usize {
+        if self.last_upgraded_pair().is_empty() {
+            return 0;
+        }
+
+        let last_upgraded_pair = self.last_upgraded_pair().get();
+        self.pair_map()
+            .values()
+            .position(|pair_address| pair_address == last_upgraded_pair)
+            .map_or(0, |index| index + 1)
+    }
+
+    #[view(getPairUpgradeProgress)]
+    fn get_pair_upgrade_progress(&self) -> MultiResult2<usize, usize> {
+        (self.get_pair_upgrade_start_index(), self.pair_map().len()).into()
+    }
+}
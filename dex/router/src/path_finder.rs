@@ -0,0 +1,329 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use super::egld_wrapper;
+use super::events;
+use super::factory;
+use super::pair_manager;
+use super::state;
+
+use factory::PairTokens;
+use pair::ProxyTrait as _;
+use pair_manager::SwapOperationType;
+
+const MAX_PATH_HOPS: usize = 4;
+const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
+const SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME: &[u8] = b"swapTokensFixedOutput";
+
+type BestPathResultType<M> = MultiResult2<BigUint<M>, MultiResultVec<SwapOperationType<M>>>;
+
+pub struct PathPair<M: ManagedTypeApi> {
+    address: ManagedAddress<M>,
+    first_token_id: TokenIdentifier<M>,
+    second_token_id: TokenIdentifier<M>,
+    first_token_reserve: BigUint<M>,
+    second_token_reserve: BigUint<M>,
+}
+
+#[derive(Clone)]
+pub struct PathStep<M: ManagedTypeApi> {
+    pair_address: ManagedAddress<M>,
+    token_wanted: TokenIdentifier<M>,
+    amount: BigUint<M>,
+}
+
+pub struct BestPath<M: ManagedTypeApi> {
+    amount: BigUint<M>,
+    steps: Vec<PathStep<M>>,
+}
+
+pub struct PathSearch<M: ManagedTypeApi> {
+    steps: Vec<PathStep<M>>,
+    visited_tokens: Vec<TokenIdentifier<M>>,
+    best_path: Option<BestPath<M>>,
+}
+
+#[elrond_wasm::module]
+pub trait PathFinderModule:
+    state::StateModule
+    + factory::FactoryModule
+    + pair_manager::PairManagerModule
+    + egld_wrapper::EgldWrapperModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + deadline::DeadlineModule
+{
+    // Returns the highest amount out over all the paths of at most max_hops pairs, followed
+    // by the swap operations for multiPairSwap. The amount wanted of each operation is the
+    // expected amount out, so callers should lower them to allow for slippage.
+    #[view(getBestPathAmountOut)]
+    fn get_best_path_amount_out(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        token_out: TokenIdentifier,
+        max_hops: usize,
+    ) -> SCResult<BestPathResultType<Self::Api>> {
+        require!(amount_in > 0, "Zero input");
+        self.require_valid_path_query(&token_in, &token_out, max_hops)?;
+
+        let pairs = self.get_path_pairs();
+        let mut search = PathSearch {
+            steps: Vec::new(),
+            visited_tokens: Vec::new(),
+            best_path: None,
+        };
+        search.visited_tokens.push(token_in.clone());
+        self.search_best_path_amount_out(
+            &pairs,
+            &token_in,
+            &amount_in,
+            &token_out,
+            max_hops,
+            &mut search,
+        );
+
+        match search.best_path {
+            Some(best_path) => {
+                Ok(self.build_best_path_result(best_path, SWAP_TOKENS_FIXED_INPUT_FUNC_NAME))
+            }
+            None => sc_error!("No path found"),
+        }
+    }
+
+    // Returns the lowest amount in over all the paths of at most max_hops pairs, followed
+    // by the swap operations for multiPairSwap, to be paid with exactly that amount.
+    #[view(getBestPathAmountIn)]
+    fn get_best_path_amount_in(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+        max_hops: usize,
+    ) -> SCResult<BestPathResultType<Self::Api>> {
+        require!(amount_out > 0, "Zero output");
+        self.require_valid_path_query(&token_in, &token_out, max_hops)?;
+
+        let pairs = self.get_path_pairs();
+        let mut search = PathSearch {
+            steps: Vec::new(),
+            visited_tokens: Vec::new(),
+            best_path: None,
+        };
+        search.visited_tokens.push(token_out.clone());
+        self.search_best_path_amount_in(
+            &pairs,
+            &token_in,
+            &token_out,
+            &amount_out,
+            max_hops,
+            &mut search,
+        );
+
+        match search.best_path {
+            Some(best_path) => {
+                Ok(self.build_best_path_result(best_path, SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME))
+            }
+            None => sc_error!("No path found"),
+        }
+    }
+
+    fn require_valid_path_query(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        max_hops: usize,
+    ) -> SCResult<()> {
+        require!(token_in != token_out, "Tokens should differ");
+        require!(
+            max_hops > 0 && max_hops <= MAX_PATH_HOPS,
+            "Invalid max hops"
+        );
+        Ok(())
+    }
+
+    // Pairs without liquidity are left out, since they cannot be part of any path, and so are
+    // deprecated pairs, since they no longer allow swaps. Legacy pairs not migrated yet are
+    // part of the paths as well.
+    fn get_path_pairs(&self) -> Vec<PathPair<Self::Api>> {
+        let mut pairs = Vec::new();
+        let pair_map = self.pair_map();
+        let legacy_pair_map = self.legacy_pair_map();
+        let pair_entries = pair_map
+            .iter()
+            .map(|(pair_key, address)| {
+                let tokens = PairTokens {
+                    first_token_id: pair_key.first_token_id,
+                    second_token_id: pair_key.second_token_id,
+                };
+                (tokens, address)
+            })
+            .chain(legacy_pair_map.iter());
+        for (tokens, address) in pair_entries {
+            if self.deprecated_pairs().contains_key(&address) {
+                continue;
+            }
+
+            let (first_token_reserve, second_token_reserve, _) = self
+                .pair_contract_proxy(address.clone())
+                .get_reserves_and_total_supply()
+                .execute_on_dest_context()
+                .into_tuple();
+            if first_token_reserve == 0 || second_token_reserve == 0 {
+                continue;
+            }
+
+            pairs.push(PathPair {
+                address,
+                first_token_id: tokens.first_token_id,
+                second_token_id: tokens.second_token_id,
+                first_token_reserve,
+                second_token_reserve,
+            });
+        }
+        pairs
+    }
+
+    // Walks the paths forward from token in, with the amount each pair gives for the previous one.
+    fn search_best_path_amount_out(
+        &self,
+        pairs: &[PathPair<Self::Api>],
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        token_out: &TokenIdentifier,
+        hops_left: usize,
+        search: &mut PathSearch<Self::Api>,
+    ) {
+        for pair in pairs.iter() {
+            let token_wanted = if &pair.first_token_id == token_in {
+                &pair.second_token_id
+            } else if &pair.second_token_id == token_in {
+                &pair.first_token_id
+            } else {
+                continue;
+            };
+            if search.visited_tokens.contains(token_wanted) {
+                continue;
+            }
+
+            let amount_out: BigUint = self
+                .pair_contract_proxy(pair.address.clone())
+                .get_amount_out_view(token_in.clone(), amount_in.clone())
+                .execute_on_dest_context();
+            if amount_out == 0 {
+                continue;
+            }
+
+            search.steps.push(PathStep {
+                pair_address: pair.address.clone(),
+                token_wanted: token_wanted.clone(),
+                amount: amount_out.clone(),
+            });
+            if token_wanted == token_out {
+                let is_better = match &search.best_path {
+                    Some(best_path) => amount_out > best_path.amount,
+                    None => true,
+                };
+                if is_better {
+                    search.best_path = Some(BestPath {
+                        amount: amount_out,
+                        steps: search.steps.clone(),
+                    });
+                }
+            } else if hops_left > 1 {
+                search.visited_tokens.push(token_wanted.clone());
+                self.search_best_path_amount_out(
+                    pairs,
+                    token_wanted,
+                    &amount_out,
+                    token_out,
+                    hops_left - 1,
+                    search,
+                );
+                search.visited_tokens.pop();
+            }
+            search.steps.pop();
+        }
+    }
+
+    // Walks the paths backwards from token out, with the amount each pair needs for the next one.
+    fn search_best_path_amount_in(
+        &self,
+        pairs: &[PathPair<Self::Api>],
+        token_in: &TokenIdentifier,
+        token_wanted: &TokenIdentifier,
+        amount_wanted: &BigUint,
+        hops_left: usize,
+        search: &mut PathSearch<Self::Api>,
+    ) {
+        for pair in pairs.iter() {
+            let (token_paid, reserve_wanted) = if &pair.first_token_id == token_wanted {
+                (&pair.second_token_id, &pair.first_token_reserve)
+            } else if &pair.second_token_id == token_wanted {
+                (&pair.first_token_id, &pair.second_token_reserve)
+            } else {
+                continue;
+            };
+            if search.visited_tokens.contains(token_paid) || reserve_wanted <= amount_wanted {
+                continue;
+            }
+
+            let amount_in: BigUint = self
+                .pair_contract_proxy(pair.address.clone())
+                .get_amount_in_view(token_wanted.clone(), amount_wanted.clone())
+                .execute_on_dest_context();
+
+            search.steps.push(PathStep {
+                pair_address: pair.address.clone(),
+                token_wanted: token_wanted.clone(),
+                amount: amount_wanted.clone(),
+            });
+            if token_paid == token_in {
+                let is_better = match &search.best_path {
+                    Some(best_path) => amount_in < best_path.amount,
+                    None => true,
+                };
+                if is_better {
+                    let mut path_steps = search.steps.clone();
+                    path_steps.reverse();
+                    search.best_path = Some(BestPath {
+                        amount: amount_in,
+                        steps: path_steps,
+                    });
+                }
+            } else if hops_left > 1 {
+                search.visited_tokens.push(token_paid.clone());
+                self.search_best_path_amount_in(
+                    pairs,
+                    token_in,
+                    token_paid,
+                    &amount_in,
+                    hops_left - 1,
+                    search,
+                );
+                search.visited_tokens.pop();
+            }
+            search.steps.pop();
+        }
+    }
+
+    fn build_best_path_result(
+        &self,
+        best_path: BestPath<Self::Api>,
+        function: &[u8],
+    ) -> BestPathResultType<Self::Api> {
+        let mut swap_operations = Vec::new();
+        for step in best_path.steps.into_iter() {
+            swap_operations.push(
+                (
+                    step.pair_address,
+                    BoxedBytes::from(function),
+                    step.token_wanted,
+                    step.amount,
+                )
+                    .into(),
+            );
+        }
+        (best_path.amount, swap_operations.into()).into()
+    }
+}
@@ -25,6 +25,16 @@ fn check_fee_enabled_after_swap_go() {
     elrond_wasm_debug::mandos_go("mandos/check_fee_enabled_after_swap.scen.json");
 }
 
+#[test]
+fn circuit_breaker_go() {
+    elrond_wasm_debug::mandos_go("mandos/circuit_breaker.scen.json");
+}
+
+#[test]
+fn circuit_breaker_flash_swap_go() {
+    elrond_wasm_debug::mandos_go("mandos/circuit_breaker_flash_swap.scen.json");
+}
+
 #[test]
 fn claim_rewards_go() {
     elrond_wasm_debug::mandos_go("mandos/claim_rewards.scen.json");
@@ -40,11 +50,46 @@ fn compound_rewards_setup_go() {
     elrond_wasm_debug::mandos_go("mandos/compound_rewards.scen.json");
 }
 
+#[test]
+fn create_pair_fee_tiers_go() {
+    elrond_wasm_debug::mandos_go("mandos/create_pair_fee_tiers.scen.json");
+}
+
 #[test]
 fn create_pair_twice_go() {
     elrond_wasm_debug::mandos_go("mandos/create_pair_twice.scen.json");
 }
 
+#[test]
+fn dca_cancel_go() {
+    elrond_wasm_debug::mandos_go("mandos/dca_cancel.scen.json");
+}
+
+#[test]
+fn dca_create_go() {
+    elrond_wasm_debug::mandos_go("mandos/dca_create.scen.json");
+}
+
+#[test]
+fn dca_execute_go() {
+    elrond_wasm_debug::mandos_go("mandos/dca_execute.scen.json");
+}
+
+#[test]
+fn delist_pair_go() {
+    elrond_wasm_debug::mandos_go("mandos/delist_pair.scen.json");
+}
+
+#[test]
+fn delist_pair_farm_go() {
+    elrond_wasm_debug::mandos_go("mandos/delist_pair_farm.scen.json");
+}
+
+#[test]
+fn egld_swap_go() {
+    elrond_wasm_debug::mandos_go("mandos/egld_swap.scen.json");
+}
+
 #[test]
 fn enter_farm_go() {
     elrond_wasm_debug::mandos_go("mandos/enter_farm.scen.json");
@@ -70,6 +115,16 @@ fn exit_mex_farm_go() {
     elrond_wasm_debug::mandos_go("mandos/exit_mex_farm.scen.json");
 }
 
+#[test]
+fn farm_factory_go() {
+    elrond_wasm_debug::mandos_go("mandos/farm_factory.scen.json");
+}
+
+#[test]
+fn farm_factory_fee_shares_go() {
+    elrond_wasm_debug::mandos_go("mandos/farm_factory_fee_shares.scen.json");
+}
+
 #[test]
 fn farm_reward_distr_scen_1_go() {
     elrond_wasm_debug::mandos_go("mandos/farm_reward_distr_scen_1.scen.json");
@@ -105,6 +160,26 @@ fn farm_wrong_lp_token_go() {
     elrond_wasm_debug::mandos_go("mandos/farm_wrong_lp_token.scen.json");
 }
 
+#[test]
+fn fee_destination_shares_go() {
+    elrond_wasm_debug::mandos_go("mandos/fee_destination_shares.scen.json");
+}
+
+#[test]
+fn fee_mode_deferred_go() {
+    elrond_wasm_debug::mandos_go("mandos/fee_mode_deferred.scen.json");
+}
+
+#[test]
+fn fee_mode_liquidity_mint_go() {
+    elrond_wasm_debug::mandos_go("mandos/fee_mode_liquidity_mint.scen.json");
+}
+
+#[test]
+fn flash_swap_go() {
+    elrond_wasm_debug::mandos_go("mandos/flash_swap.scen.json");
+}
+
 #[test]
 fn get_amounts_go() {
     elrond_wasm_debug::mandos_go("mandos/get_amounts.scen.json");
@@ -125,6 +200,21 @@ fn get_pair_views_go() {
     elrond_wasm_debug::mandos_go("mandos/get_pair_views.scen.json");
 }
 
+#[test]
+fn limit_order_cancel_go() {
+    elrond_wasm_debug::mandos_go("mandos/limit_order_cancel.scen.json");
+}
+
+#[test]
+fn limit_order_create_go() {
+    elrond_wasm_debug::mandos_go("mandos/limit_order_create.scen.json");
+}
+
+#[test]
+fn limit_order_fill_go() {
+    elrond_wasm_debug::mandos_go("mandos/limit_order_fill.scen.json");
+}
+
 #[test]
 fn multi_pair_swap_go() {
     elrond_wasm_debug::mandos_go("mandos/multi_pair_swap.scen.json");
@@ -135,6 +225,21 @@ fn merge_tokens_go() {
     elrond_wasm_debug::mandos_go("mandos/merge_tokens.scen.json");
 }
 
+#[test]
+fn migrate_pair_map_go() {
+    elrond_wasm_debug::mandos_go("mandos/migrate_pair_map.scen.json");
+}
+
+#[test]
+fn multi_path_swap_go() {
+    elrond_wasm_debug::mandos_go("mandos/multi_path_swap.scen.json");
+}
+
+#[test]
+fn oracle_config_go() {
+    elrond_wasm_debug::mandos_go("mandos/oracle_config.scen.json");
+}
+
 #[test]
 fn owner_pause_farm_go() {
     elrond_wasm_debug::mandos_go("mandos/owner_pause_farm.scen.json");
@@ -145,6 +250,16 @@ fn owner_resume_farm_go() {
     elrond_wasm_debug::mandos_go("mandos/owner_resume_farm.scen.json");
 }
 
+#[test]
+fn pair_setup_go() {
+    elrond_wasm_debug::mandos_go("mandos/pair_setup.scen.json");
+}
+
+#[test]
+fn price_cumulative_go() {
+    elrond_wasm_debug::mandos_go("mandos/price_cumulative.scen.json");
+}
+
 #[test]
 fn remove_liquidity_go() {
     elrond_wasm_debug::mandos_go("mandos/remove_liquidity.scen.json");
@@ -155,6 +270,11 @@ fn remove_liquidity_twice_go() {
     elrond_wasm_debug::mandos_go("mandos/remove_liquidity_twice.scen.json");
 }
 
+#[test]
+fn router_best_path_go() {
+    elrond_wasm_debug::mandos_go("mandos/router_best_path.scen.json");
+}
+
 #[test]
 fn router_pause_self_go() {
     elrond_wasm_debug::mandos_go("mandos/router_pause_self.scen.json");
@@ -165,6 +285,26 @@ fn router_resume_self_go() {
     elrond_wasm_debug::mandos_go("mandos/router_resume_self.scen.json");
 }
 
+#[test]
+fn set_token_weights_go() {
+    elrond_wasm_debug::mandos_go("mandos/set_token_weights.scen.json");
+}
+
+#[test]
+fn single_token_liquidity_go() {
+    elrond_wasm_debug::mandos_go("mandos/single_token_liquidity.scen.json");
+}
+
+#[test]
+fn stableswap_get_amounts_go() {
+    elrond_wasm_debug::mandos_go("mandos/stableswap_get_amounts.scen.json");
+}
+
+#[test]
+fn swap_deadline_go() {
+    elrond_wasm_debug::mandos_go("mandos/swap_deadline.scen.json");
+}
+
 #[test]
 fn swap_fixed_input_go() {
     elrond_wasm_debug::mandos_go("mandos/swap_fixed_input.scen.json");
@@ -180,6 +320,11 @@ fn swap_fixed_output_go() {
     elrond_wasm_debug::mandos_go("mandos/swap_fixed_output.scen.json");
 }
 
+#[test]
+fn swap_quote_go() {
+    elrond_wasm_debug::mandos_go("mandos/swap_quote.scen.json");
+}
+
 #[test]
 fn swap_same_token_go() {
     elrond_wasm_debug::mandos_go("mandos/swap_same_token.scen.json");
@@ -190,7 +335,27 @@ fn swap_wrong_token_go() {
     elrond_wasm_debug::mandos_go("mandos/swap_wrong_token.scen.json");
 }
 
+#[test]
+fn sync_skim_go() {
+    elrond_wasm_debug::mandos_go("mandos/sync_skim.scen.json");
+}
+
+#[test]
+fn twap_go() {
+    elrond_wasm_debug::mandos_go("mandos/twap.scen.json");
+}
+
+#[test]
+fn upgrade_all_pairs_go() {
+    elrond_wasm_debug::mandos_go("mandos/upgrade_all_pairs.scen.json");
+}
+
 #[test]
 fn upgrade_contract_go() {
     elrond_wasm_debug::mandos_go("mandos/upgrade_contract.scen.json");
 }
+
+#[test]
+fn weighted_get_amounts_go() {
+    elrond_wasm_debug::mandos_go("mandos/weighted_get_amounts.scen.json");
+}
@@ -116,7 +116,7 @@ pub trait LockedAssetTokenMergeModule:
                         milestone.unlock_epoch,
                         self.rule_of_three(
                             &self.types().big_uint_from(milestone.unlock_percent as u64),
-                            &self.types().big_uint_from(PERCENTAGE_TOTAL as u64),
+                            &self.types().big_uint_from(PERCENTAGE_TOTAL),
                             &locked_token.token_amount.amount,
                         ),
                     ))
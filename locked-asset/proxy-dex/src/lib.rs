@@ -25,6 +25,7 @@ pub trait ProxyDexImpl:
     + token_supply::TokenSupplyModule
     + token_merge::TokenMergeModule
     + token_send::TokenSendModule
+    + deadline::DeadlineModule
     + wrapped_farm_token_merge::WrappedFarmTokenMerge
     + wrapped_lp_token_merge::WrappedLpTokenMerge
     + events::EventsModule
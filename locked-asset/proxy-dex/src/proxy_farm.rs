@@ -36,6 +36,7 @@ pub trait ProxyFarmModule:
     + wrapped_farm_token_merge::WrappedFarmTokenMerge
     + wrapped_lp_token_merge::WrappedLpTokenMerge
     + events::EventsModule
+    + deadline::DeadlineModule
 {
     #[proxy]
     fn farm_contract_proxy(&self, to: ManagedAddress) -> farm::Proxy<Self::Api>;
@@ -8,6 +8,7 @@ elrond_wasm::derive_imports!();
 use proxy_common::ACCEPT_PAY_FUNC_NAME;
 
 use common_structs::{Nonce, WrappedLpTokenAttributes};
+use deadline::NO_DEADLINE;
 use pair::config::ProxyTrait as _;
 
 use super::events;
@@ -33,6 +34,7 @@ pub trait ProxyPairModule:
     + wrapped_lp_token_merge::WrappedLpTokenMerge
     + token_merge::TokenMergeModule
     + token_send::TokenSendModule
+    + deadline::DeadlineModule
     + events::EventsModule
 {
     #[proxy]
@@ -59,7 +61,9 @@ pub trait ProxyPairModule:
         pair_address: ManagedAddress,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
     ) -> SCResult<()> {
+        self.require_deadline_not_passed(&opt_deadline)?;
         self.require_is_intermediated_pair(&pair_address)?;
         self.require_wrapped_lp_token_id_not_empty()?;
 
@@ -175,7 +179,9 @@ pub trait ProxyPairModule:
         pair_address: ManagedAddress,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        #[var_args] opt_deadline: OptionalArg<u64>,
     ) -> SCResult<()> {
+        self.require_deadline_not_passed(&opt_deadline)?;
         self.require_is_intermediated_pair(&pair_address)?;
         self.require_wrapped_lp_token_id_not_empty()?;
         require!(token_nonce != 0, "Can only be called with an SFT");
@@ -296,6 +302,7 @@ pub trait ProxyPairModule:
             .add_liquidity(
                 first_token_amount_min.clone(),
                 second_token_amount_min.clone(),
+                OptionalArg::Some(NO_DEADLINE),
                 OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
             )
             .with_multi_token_transfer(all_token_payments)
@@ -316,6 +323,7 @@ pub trait ProxyPairModule:
                 liquidity.clone(),
                 first_token_amount_min.clone(),
                 second_token_amount_min.clone(),
+                OptionalArg::Some(NO_DEADLINE),
                 OptionalArg::Some(BoxedBytes::from(ACCEPT_PAY_FUNC_NAME)),
             )
             .execute_on_dest_context()